@@ -3,8 +3,32 @@ pub mod utils;
 mod week_01;
 mod week_02;
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    week_01::run()?;
-    week_02::run()?;
+const USAGE: &str = "usage: aoc2020 [<day> <tool> [args...]]";
+
+/// Without arguments, solves all the puzzles. Otherwise runs one of the
+/// additional tools as `<day> <tool> [args...]`.
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.split_first() {
+        None => week_01::run().and_then(|()| week_02::run()),
+        Some((day, [])) => Err(format!("missing tool for day {}\n{}", day, USAGE).into()),
+        Some((day, args)) => tool(day, args),
+    };
+    if let Err(err) = result {
+        // stop quietly when the output is piped into something like `head`
+        if let Some(err) = err.downcast_ref::<std::io::Error>() {
+            if err.kind() == std::io::ErrorKind::BrokenPipe {
+                return;
+            }
+        }
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+}
+
+fn tool(day: &str, args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    if !week_01::tool(day, args)? && !week_02::tool(day, args)? {
+        return Err(format!("unknown tool: {} {}\n{}", day, args[0], USAGE).into());
+    }
     Ok(())
 }
@@ -1,3 +1,7 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
 pub fn split<'a>(input: &'a str, search: &str) -> Option<(&'a str, &'a str)> {
     let mut iter = input.splitn(2, search);
     let fst = iter.next();
@@ -5,11 +9,39 @@ pub fn split<'a>(input: &'a str, search: &str) -> Option<(&'a str, &'a str)> {
     fst.and_then(|fst| snd.map(|snd| (fst, snd)))
 }
 
+/// Splits the input into records that are separated by one or more blank
+/// lines, yielding the lines of each record.
+///
+/// Leading and trailing blank lines are ignored, and `\r\n` line endings are
+/// handled as well.
+pub fn records(input: &str) -> impl Iterator<Item = Vec<&str>> {
+    let mut lines = input
+        .lines()
+        .map(|line| line.strip_suffix('\r').unwrap_or(line));
+    std::iter::from_fn(move || {
+        let record: Vec<_> = lines
+            .by_ref()
+            .skip_while(|line| line.is_empty())
+            .take_while(|line| !line.is_empty())
+            .collect();
+        if record.is_empty() {
+            None
+        } else {
+            Some(record)
+        }
+    })
+}
+
 fn get(ex: &str) -> std::io::Result<String> {
-    let path = std::path::Path::new("inputs").join(ex);
+    let path = Path::new("inputs").join(ex);
     std::fs::read_to_string(path)
 }
 
+/// The path of the real input for the given day.
+fn input_path(day: &str) -> PathBuf {
+    Path::new("inputs").join(format!("input-{}.txt", day))
+}
+
 /// Get the example input for the given day.
 pub fn example(day: &str) -> std::io::Result<String> {
     get(&format!("example-{}.txt", day))
@@ -17,5 +49,192 @@ pub fn example(day: &str) -> std::io::Result<String> {
 
 /// Get the real input for the given day.
 pub fn input(day: &str) -> std::io::Result<String> {
-    get(&format!("input-{}.txt", day))
+    std::fs::read_to_string(input_path(day))
+}
+
+/// Get the input of a tool invocation, which is either the given file, stdin
+/// for `-`, or the real input for the given day.
+pub fn read_input(day: &str, path: Option<&String>) -> std::io::Result<String> {
+    match path.map(String::as_str) {
+        Some("-") => std::io::read_to_string(std::io::stdin()),
+        Some(path) => std::fs::read_to_string(path),
+        None => input(day),
+    }
+}
+
+/// Like `read_input`, but opens the input for reading it as it arrives.
+pub fn open_input(day: &str, path: Option<&String>) -> std::io::Result<Box<dyn BufRead>> {
+    Ok(match path.map(String::as_str) {
+        Some("-") => Box::new(std::io::stdin().lock()),
+        Some(path) => Box::new(BufReader::new(File::open(path)?)),
+        None => Box::new(BufReader::new(File::open(input_path(day))?)),
+    })
+}
+
+/// An arbitrary-precision unsigned integer, supporting just enough operations
+/// to count things exactly.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BigUint {
+    /// Little-endian base 2^32 digits, without any trailing zeros.
+    digits: Vec<u32>,
+}
+
+impl BigUint {
+    /// Adds `other * factor` to `self`.
+    pub fn add_mul(&mut self, other: &BigUint, factor: usize) {
+        if self.digits.len() < other.digits.len() {
+            self.digits.resize(other.digits.len(), 0);
+        }
+        let mut carry = 0u128;
+        for (i, digit) in self.digits.iter_mut().enumerate() {
+            let product = other.digits.get(i).copied().unwrap_or(0) as u128 * factor as u128;
+            let sum = *digit as u128 + product + carry;
+            *digit = sum as u32;
+            carry = sum >> 32;
+            if carry == 0 && i >= other.digits.len() {
+                break;
+            }
+        }
+        while carry > 0 {
+            self.digits.push(carry as u32);
+            carry >>= 32;
+        }
+        while self.digits.last() == Some(&0) {
+            self.digits.pop();
+        }
+    }
+}
+
+impl From<usize> for BigUint {
+    fn from(mut num: usize) -> Self {
+        let mut digits = vec![];
+        while num > 0 {
+            digits.push(num as u32);
+            num = num.checked_shr(32).unwrap_or(0);
+        }
+        Self { digits }
+    }
+}
+
+impl std::fmt::Display for BigUint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        const CHUNK: u64 = 1_000_000_000;
+
+        // repeatedly divide by `CHUNK`, collecting the remainders
+        let mut chunks = vec![];
+        let mut digits = self.digits.clone();
+        while !digits.is_empty() {
+            let mut remainder = 0u64;
+            for digit in digits.iter_mut().rev() {
+                let value = (remainder << 32) | *digit as u64;
+                *digit = (value / CHUNK) as u32;
+                remainder = value % CHUNK;
+            }
+            chunks.push(remainder);
+            while digits.last() == Some(&0) {
+                digits.pop();
+            }
+        }
+
+        match chunks.split_last() {
+            None => write!(f, "0"),
+            Some((first, rest)) => {
+                write!(f, "{}", first)?;
+                for chunk in rest.iter().rev() {
+                    write!(f, "{:09}", chunk)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Finds the strongly connected components of a graph that contain a cycle,
+/// that is every component with more than one node, and the nodes that point
+/// at themselves. The nodes of each component are sorted.
+///
+/// This is an iterative version of Tarjan's algorithm, so that long paths do
+/// not overflow the stack.
+pub fn cyclic_components<I>(len: usize, successors: impl Fn(usize) -> I) -> Vec<Vec<usize>>
+where
+    I: IntoIterator<Item = usize>,
+{
+    let mut index = vec![usize::MAX; len];
+    let mut lowlink = vec![0; len];
+    let mut on_stack = vec![false; len];
+    let mut stack = vec![];
+    let mut components = vec![];
+    let mut counter = 0;
+
+    for root in 0..len {
+        if index[root] != usize::MAX {
+            continue;
+        }
+        index[root] = counter;
+        lowlink[root] = counter;
+        counter += 1;
+        stack.push(root);
+        on_stack[root] = true;
+        // the current path, with the successors that are left to visit
+        let mut path = vec![(root, successors(root).into_iter())];
+        while let Some((node, next)) = path.last_mut() {
+            let node = *node;
+            if let Some(child) = next.next() {
+                if index[child] == usize::MAX {
+                    index[child] = counter;
+                    lowlink[child] = counter;
+                    counter += 1;
+                    stack.push(child);
+                    on_stack[child] = true;
+                    path.push((child, successors(child).into_iter()));
+                } else if on_stack[child] {
+                    lowlink[node] = lowlink[node].min(index[child]);
+                }
+                continue;
+            }
+
+            path.pop();
+            if let Some((parent, _)) = path.last() {
+                lowlink[*parent] = lowlink[*parent].min(lowlink[node]);
+            }
+            if lowlink[node] == index[node] {
+                let mut component = vec![];
+                while let Some(member) = stack.pop() {
+                    on_stack[member] = false;
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                if component.len() > 1 || successors(node).into_iter().any(|n| n == node) {
+                    component.sort_unstable();
+                    components.push(component);
+                }
+            }
+        }
+    }
+    components
+}
+
+/// A small xorshift pseudorandom number generator, good enough for generating
+/// inputs and sampling.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // the state must never be zero
+        Rng((seed ^ 0x9e37_79b9_7f4a_7c15) | 1)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// A number in `0..n`, which must not be empty.
+    pub fn below(&mut self, n: u64) -> u64 {
+        ((self.next_u64() as u128 * n as u128) >> 64) as u64
+    }
 }
@@ -1,4 +1,8 @@
-use crate::utils::{example, input};
+use std::hint::black_box;
+use std::io::Write;
+use std::time::Instant;
+
+use crate::utils::{cyclic_components, example, input, read_input, records, BigUint};
 
 mod day_01 {
     use std::collections::HashSet;
@@ -142,26 +146,21 @@ mod day_03 {
 mod day_04 {
     use std::collections::HashMap;
 
-    use crate::utils::split;
+    use crate::utils::{records, split};
 
     const REQUIRED: &[&str] = &["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid"]; // "cid"
     const ECL: &[&str] = &["amb", "blu", "brn", "gry", "grn", "hzl", "oth"];
 
     fn parse_passports(input: &str) -> Vec<HashMap<&str, &str>> {
-        let mut passports = vec![];
-        let mut passport = HashMap::new();
-        for line in input.lines() {
-            if line.is_empty() {
-                passports.push(std::mem::take(&mut passport));
-            }
-            for kv in line.split_whitespace() {
-                if let Some((k, v)) = split(kv, ":") {
-                    passport.insert(k, v);
-                }
-            }
-        }
-        passports.push(passport);
-        passports
+        records(input)
+            .map(|record| {
+                record
+                    .iter()
+                    .flat_map(|line| line.split_whitespace())
+                    .filter_map(|kv| split(kv, ":"))
+                    .collect()
+            })
+            .collect()
     }
 
     fn is_valid_a(pp: &HashMap<&str, &str>) -> bool {
@@ -170,27 +169,27 @@ mod day_04 {
 
     fn is_valid_b(pp: HashMap<&str, &str>) -> Option<bool> {
         let byr: usize = pp.get("byr")?.parse().ok()?;
-        if byr < 1920 || byr > 2002 {
+        if !(1920..=2002).contains(&byr) {
             return None;
         }
         let iyr: usize = pp.get("iyr")?.parse().ok()?;
-        if iyr < 2010 || iyr > 2020 {
+        if !(2010..=2020).contains(&iyr) {
             return None;
         }
         let eyr: usize = pp.get("eyr")?.parse().ok()?;
-        if eyr < 2020 || eyr > 2030 {
+        if !(2020..=2030).contains(&eyr) {
             return None;
         }
 
         let hgt = pp.get("hgt")?;
         if let Some(hgt) = hgt.strip_suffix("cm") {
             let cm: usize = hgt.parse().ok()?;
-            if cm < 150 || cm > 193 {
+            if !(150..=193).contains(&cm) {
                 return None;
             }
         } else if let Some(hgt) = hgt.strip_suffix("in") {
             let inch: usize = hgt.parse().ok()?;
-            if inch < 59 || inch > 76 {
+            if !(59..=76).contains(&inch) {
                 return None;
             }
         } else {
@@ -198,7 +197,7 @@ mod day_04 {
         }
 
         let hcl = pp.get("hcl")?.strip_prefix("#")?;
-        if hcl.len() != 6 || hcl.chars().any(|c| !c.is_digit(16)) {
+        if hcl.len() != 6 || hcl.chars().any(|c| !c.is_ascii_hexdigit()) {
             return None;
         }
         let ecl = pp.get("ecl")?;
@@ -230,225 +229,1342 @@ mod day_04 {
 }
 
 mod day_05 {
+    use std::fmt;
     use std::ops::Range;
 
-    const ROWS: usize = 128;
-    const COLUMNS: usize = 8;
+    /// The seating geometry of a plane, together with the characters used to
+    /// binary-partition its rows and columns.
+    #[derive(Clone, Debug, PartialEq)]
+    pub struct PlaneLayout {
+        rows: usize,
+        columns: usize,
+        front: char,
+        back: char,
+        left: char,
+        right: char,
+    }
+
+    impl PlaneLayout {
+        /// Creates a new layout with `[front, back, left, right]` partition characters.
+        ///
+        /// Both `rows` and `columns` need to be powers of two, and the partition
+        /// characters need to be distinct.
+        pub fn new(rows: usize, columns: usize, chars: [char; 4]) -> Option<Self> {
+            if !rows.is_power_of_two() || !columns.is_power_of_two() {
+                return None;
+            }
+            if chars
+                .iter()
+                .enumerate()
+                .any(|(i, c)| chars[..i].contains(c))
+            {
+                return None;
+            }
+            let [front, back, left, right] = chars;
+            Some(Self {
+                rows,
+                columns,
+                front,
+                back,
+                left,
+                right,
+            })
+        }
+
+        /// The number of characters a boarding pass for this layout has.
+        fn code_len(&self) -> usize {
+            (self.rows.trailing_zeros() + self.columns.trailing_zeros()) as usize
+        }
+    }
+
+    impl Default for PlaneLayout {
+        fn default() -> Self {
+            Self {
+                rows: 128,
+                columns: 8,
+                front: 'F',
+                back: 'B',
+                left: 'L',
+                right: 'R',
+            }
+        }
+    }
 
     fn len(range: &Range<usize>) -> usize {
         range.end - range.start
     }
 
-    fn decode_boarding_pass(input: &str) -> (usize, usize) {
-        let mut row = 0..ROWS;
-        let mut column = 0..COLUMNS;
-        for c in input.chars() {
-            match c {
-                'F' => {
-                    row.end -= len(&row) / 2;
+    /// The reasons why a single boarding pass can not be decoded.
+    #[derive(Debug, PartialEq)]
+    pub enum DecodeError {
+        /// The character at `index` is not one of the layouts partition characters.
+        InvalidChar { index: usize, c: char },
+        /// The character at `index` partitions an axis that is already fully decoded.
+        TooManyPartitions { index: usize, c: char },
+        /// The pass ended before both axes were fully decoded.
+        TooShort { len: usize, expected: usize },
+    }
+
+    impl fmt::Display for DecodeError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                DecodeError::InvalidChar { index, c } => {
+                    write!(f, "invalid character {:?} at index {}", c, index)
                 }
-                'B' => {
-                    row.start += len(&row) / 2;
+                DecodeError::TooManyPartitions { index, c } => {
+                    write!(f, "superfluous partition {:?} at index {}", c, index)
                 }
-                'L' => {
-                    column.end -= len(&column) / 2;
+                DecodeError::TooShort { len, expected } => {
+                    write!(f, "expected {} characters, got {}", expected, len)
                 }
-                'R' => {
-                    column.start += len(&column) / 2;
+            }
+        }
+    }
+
+    /// A boarding pass of the input that failed to decode.
+    #[derive(Debug)]
+    pub struct InvalidPass {
+        line: usize,
+        pass: String,
+        error: DecodeError,
+    }
+
+    impl fmt::Display for InvalidPass {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(
+                f,
+                "invalid boarding pass {:?} on line {}: {}",
+                self.pass, self.line, self.error
+            )
+        }
+    }
+
+    impl std::error::Error for InvalidPass {}
+
+    /// All the boarding passes of the input that failed to decode.
+    #[derive(Debug)]
+    pub struct InvalidPasses(Vec<InvalidPass>);
+
+    impl fmt::Display for InvalidPasses {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            for (i, pass) in self.0.iter().enumerate() {
+                if i > 0 {
+                    writeln!(f)?;
                 }
-                _ => unreachable!(),
+                write!(f, "{}", pass)?;
+            }
+            Ok(())
+        }
+    }
+
+    impl std::error::Error for InvalidPasses {}
+
+    fn decode_boarding_pass(
+        layout: &PlaneLayout,
+        input: &str,
+    ) -> Result<(usize, usize), DecodeError> {
+        let mut row = 0..layout.rows;
+        let mut column = 0..layout.columns;
+        for (index, c) in input.chars().enumerate() {
+            let axis = match c {
+                c if c == layout.front || c == layout.back => &mut row,
+                c if c == layout.left || c == layout.right => &mut column,
+                c => return Err(DecodeError::InvalidChar { index, c }),
+            };
+            let half = len(axis) / 2;
+            if half == 0 {
+                return Err(DecodeError::TooManyPartitions { index, c });
+            }
+            if c == layout.front || c == layout.left {
+                axis.end -= half;
+            } else {
+                axis.start += half;
             }
         }
 
-        (row.start, column.start)
+        if len(&row) != 1 || len(&column) != 1 {
+            return Err(DecodeError::TooShort {
+                len: input.chars().count(),
+                expected: layout.code_len(),
+            });
+        }
+
+        Ok((row.start, column.start))
     }
 
-    fn seat_id((row, column): (usize, usize)) -> usize {
-        row * COLUMNS + column
+    fn seat_id(layout: &PlaneLayout, (row, column): (usize, usize)) -> usize {
+        row * layout.columns + column
     }
 
-    pub fn a(input: &str) -> usize {
-        input
-            .lines()
-            .map(decode_boarding_pass)
-            .map(seat_id)
+    /// Decodes the seat ids of all the boarding passes in the input, or reports
+    /// all the ones that are invalid.
+    pub fn decode_seat_ids(layout: &PlaneLayout, input: &str) -> Result<Vec<usize>, InvalidPasses> {
+        let mut ids = vec![];
+        let mut invalid = vec![];
+        for (i, line) in input.lines().enumerate() {
+            match decode_boarding_pass(layout, line) {
+                Ok(seat) => ids.push(seat_id(layout, seat)),
+                Err(error) => invalid.push(InvalidPass {
+                    line: i + 1,
+                    pass: line.to_owned(),
+                    error,
+                }),
+            }
+        }
+        if invalid.is_empty() {
+            Ok(ids)
+        } else {
+            Err(InvalidPasses(invalid))
+        }
+    }
+
+    /// Decodes the seat id of a default layout boarding pass by reading it as
+    /// a binary number.
+    ///
+    /// `B` and `R` are the only ones among `FBLR` with bit 2 unset, so they
+    /// become the one bits. There is no validation whatsoever.
+    fn fast_seat_id(pass: &[u8]) -> usize {
+        pass.iter()
+            .fold(0, |id, b| (id << 1) | (!*b as usize >> 2 & 1))
+    }
+
+    /// Decodes the seat ids of a whole file of default layout boarding passes.
+    ///
+    /// The bit trick only works if every pass is exactly 10 valid characters
+    /// followed by a `\n`, so anything else falls back to `decode_seat_ids`.
+    pub fn fast_seat_ids(input: &str) -> Result<Vec<usize>, InvalidPasses> {
+        let bytes = input.as_bytes();
+        let is_pass = |pass: &[u8]| {
+            pass[..7].iter().all(|b| *b == b'F' || *b == b'B')
+                && pass[7..10].iter().all(|b| *b == b'L' || *b == b'R')
+                && pass[10] == b'\n'
+        };
+        if !bytes.len().is_multiple_of(11) || !bytes.chunks(11).all(is_pass) {
+            return decode_seat_ids(&PlaneLayout::default(), input);
+        }
+        Ok(bytes
+            .chunks(11)
+            .map(|pass| fast_seat_id(&pass[..10]))
+            .collect())
+    }
+
+    pub fn a(layout: &PlaneLayout, input: &str) -> Result<usize, InvalidPasses> {
+        Ok(decode_seat_ids(layout, input)?
+            .into_iter()
             .max()
-            .unwrap_or_default()
+            .unwrap_or_default())
     }
 
-    pub fn b(input: &str) -> usize {
-        let mut seats: Vec<_> = input
-            .lines()
-            .map(decode_boarding_pass)
-            .map(seat_id)
-            .collect();
+    pub fn b(layout: &PlaneLayout, input: &str) -> Result<usize, InvalidPasses> {
+        let mut seats = decode_seat_ids(layout, input)?;
         seats.sort_unstable();
         for win in seats.windows(2) {
             match win {
                 [a, b] if b - a == 2 => {
-                    return a + 1;
+                    return Ok(a + 1);
                 }
                 _ => {}
             }
         }
-        0
+        Ok(0)
+    }
+
+    /// The occupancy of every seat in a plane, according to a list of boarding passes.
+    pub struct SeatMap<'l> {
+        layout: &'l PlaneLayout,
+        /// The number of boarding passes for each seat id.
+        passes: Vec<usize>,
+    }
+
+    impl<'l> SeatMap<'l> {
+        pub fn new(layout: &'l PlaneLayout, input: &str) -> Result<Self, InvalidPasses> {
+            let mut passes = vec![0; layout.rows * layout.columns];
+            for id in decode_seat_ids(layout, input)? {
+                passes[id] += 1;
+            }
+            Ok(Self { layout, passes })
+        }
+
+        fn is_occupied(&self, id: usize) -> bool {
+            self.passes.get(id).copied().unwrap_or(0) > 0
+        }
+
+        /// All the seats that nobody has a boarding pass for.
+        pub fn missing(&self) -> impl Iterator<Item = usize> + '_ {
+            (0..self.passes.len()).filter(move |id| !self.is_occupied(*id))
+        }
+
+        /// All the seats that have more than one boarding pass.
+        pub fn duplicates(&self) -> impl Iterator<Item = usize> + '_ {
+            (0..self.passes.len()).filter(move |id| self.passes[*id] > 1)
+        }
+
+        /// The only empty seat that has occupied seats on both sides.
+        pub fn your_seat(&self) -> Option<usize> {
+            self.missing()
+                .find(|id| *id > 0 && self.is_occupied(id - 1) && self.is_occupied(id + 1))
+        }
+
+        /// The number of occupied seats in the given row.
+        pub fn row_occupancy(&self, row: usize) -> usize {
+            let columns = self.layout.columns;
+            (row * columns..(row + 1) * columns)
+                .filter(|id| self.is_occupied(*id))
+                .count()
+        }
+    }
+
+    impl fmt::Display for SeatMap<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let columns = self.layout.columns;
+            let your_seat = self.your_seat();
+            let width = columns.to_string().len();
+            for row in 0..self.layout.rows {
+                write!(f, "{:>4} ", row)?;
+                for id in row * columns..(row + 1) * columns {
+                    let c = match self.passes[id] {
+                        _ if Some(id) == your_seat => '@',
+                        0 => '.',
+                        1 => '#',
+                        _ => '!',
+                    };
+                    write!(f, "{}", c)?;
+                }
+                writeln!(
+                    f,
+                    " {:>width$}/{}",
+                    self.row_occupancy(row),
+                    columns,
+                    width = width
+                )?;
+            }
+            writeln!(f, "# occupied, . empty, ! duplicate, @ your seat")?;
+
+            match your_seat {
+                Some(id) => writeln!(
+                    f,
+                    "your seat: {} (row {}, column {})",
+                    id,
+                    id / columns,
+                    id % columns
+                )?,
+                None => writeln!(f, "your seat: not found")?,
+            }
+
+            // collapse the missing ids into consecutive runs
+            let mut runs: Vec<(usize, usize)> = vec![];
+            for id in self.missing() {
+                match runs.last_mut() {
+                    Some((_, end)) if *end + 1 == id => *end = id,
+                    _ => runs.push((id, id)),
+                }
+            }
+            write!(f, "missing:")?;
+            if runs.is_empty() {
+                write!(f, " none")?;
+            }
+            for (i, (start, end)) in runs.iter().copied().enumerate() {
+                write!(f, "{}", if i == 0 { " " } else { ", " })?;
+                if start == end {
+                    write!(f, "{}", start)?;
+                } else {
+                    write!(f, "{}-{}", start, end)?;
+                }
+                if start == 0 {
+                    write!(f, " (front)")?;
+                } else if end == self.passes.len() - 1 {
+                    write!(f, " (back)")?;
+                }
+            }
+            writeln!(f)?;
+
+            let duplicates: Vec<_> = self.duplicates().map(|id| id.to_string()).collect();
+            if duplicates.is_empty() {
+                writeln!(f, "duplicates: none")
+            } else {
+                writeln!(f, "duplicates: {}", duplicates.join(", "))
+            }
+        }
     }
 }
 
 mod day_06 {
-    use std::collections::{HashMap, HashSet};
+    use std::collections::BTreeMap;
+    use std::fmt;
+    use std::ops::{BitAnd, BitOr, BitXor};
+    use std::str::FromStr;
+
+    use crate::utils::records;
+
+    /// A set of the questions `a` to `z`, as a bitmask.
+    #[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+    pub struct Answers(u32);
+
+    impl Answers {
+        const ALL: Answers = Answers((1 << 26) - 1);
+
+        /// Parses the answers of a single person, ignoring anything that is not a question.
+        fn parse(line: &str) -> Self {
+            Self(
+                line.bytes()
+                    .filter(u8::is_ascii_lowercase)
+                    .fold(0, |mask, b| mask | 1 << (b - b'a')),
+            )
+        }
 
-    pub fn a(input: &str) -> usize {
-        let mut group = HashSet::new();
-        let mut sum = 0;
+        fn contains(self, question: u8) -> bool {
+            self.0 & 1 << question != 0
+        }
+
+        pub fn len(self) -> usize {
+            self.0.count_ones() as usize
+        }
+    }
+
+    impl BitOr for Answers {
+        type Output = Self;
+
+        /// The union of both sets.
+        fn bitor(self, rhs: Self) -> Self {
+            Self(self.0 | rhs.0)
+        }
+    }
+
+    impl BitAnd for Answers {
+        type Output = Self;
 
-        for line in input.lines() {
-            if line.is_empty() {
-                sum += group.len();
-                group.clear();
+        /// The intersection of both sets.
+        fn bitand(self, rhs: Self) -> Self {
+            Self(self.0 & rhs.0)
+        }
+    }
+
+    impl BitXor for Answers {
+        type Output = Self;
+
+        /// The symmetric difference of both sets.
+        fn bitxor(self, rhs: Self) -> Self {
+            Self(self.0 ^ rhs.0)
+        }
+    }
+
+    /// The answers of each person in a group.
+    struct Group(Vec<Answers>);
+
+    impl Group {
+        /// The questions anyone in the group answered.
+        fn union(&self) -> Answers {
+            self.0.iter().fold(Answers::default(), |acc, a| acc | *a)
+        }
+
+        /// The questions everyone in the group answered.
+        fn intersection(&self) -> Answers {
+            if self.0.is_empty() {
+                return Answers::default();
+            }
+            self.0.iter().fold(Answers::ALL, |acc, a| acc & *a)
+        }
+
+        /// The questions an odd number of people in the group answered.
+        fn symmetric_difference(&self) -> Answers {
+            self.0.iter().fold(Answers::default(), |acc, a| acc ^ *a)
+        }
+
+        /// The questions at least `k` people in the group answered.
+        fn at_least(&self, k: usize) -> Answers {
+            let mut answers = Answers::default();
+            for question in 0..26 {
+                let count = self.0.iter().filter(|a| a.contains(question)).count();
+                if count >= k {
+                    answers.0 |= 1 << question;
+                }
             }
+            answers
+        }
 
-            for c in line.chars() {
-                group.insert(c);
+        fn answer(&self, query: Query) -> Answers {
+            match query {
+                Query::Anyone => self.union(),
+                Query::Everyone => self.intersection(),
+                Query::Odd => self.symmetric_difference(),
+                Query::AtLeast(k) => self.at_least(k),
             }
         }
-        sum += group.len();
+    }
 
-        sum
+    /// Reads the groups, which are separated by blank lines.
+    fn parse_groups(input: &str) -> Vec<Group> {
+        records(input)
+            .map(|record| Group(record.into_iter().map(Answers::parse).collect()))
+            .collect()
     }
 
-    pub fn count_matching(map: &HashMap<char, usize>, needle: usize) -> usize {
-        map.values().copied().filter(|v| *v == needle).count()
+    /// A question that can be asked about each group.
+    #[derive(Clone, Copy, Debug)]
+    pub enum Query {
+        /// Which questions did anyone answer?
+        Anyone,
+        /// Which questions did everyone answer?
+        Everyone,
+        /// Which questions did an odd number of people answer?
+        Odd,
+        /// Which questions did at least this many people answer?
+        AtLeast(usize),
+    }
+
+    impl FromStr for Query {
+        type Err = String;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            Ok(match s {
+                "anyone" | "union" => Query::Anyone,
+                "everyone" | "intersection" => Query::Everyone,
+                "odd" | "symmetric-difference" => Query::Odd,
+                _ => {
+                    let k = s
+                        .strip_prefix("at-least-")
+                        .and_then(|k| k.parse().ok())
+                        .ok_or_else(|| format!("unknown query: {}", s))?;
+                    Query::AtLeast(k)
+                }
+            })
+        }
+    }
+
+    /// Sums up the number of questions matching the query over all the groups.
+    pub fn query(input: &str, query: Query) -> usize {
+        parse_groups(input)
+            .iter()
+            .map(|group| group.answer(query).len())
+            .sum()
+    }
+
+    pub fn a(input: &str) -> usize {
+        self::query(input, Query::Anyone)
     }
 
     pub fn b(input: &str) -> usize {
-        let mut group = HashMap::new();
-        let mut sum = 0;
-        let mut count = 0;
-
-        for line in input.lines() {
-            if line.is_empty() {
-                sum += count_matching(&group, count);
-                count = 0;
-                group.clear();
-                continue;
-            }
+        self::query(input, Query::Everyone)
+    }
 
-            for c in line.chars() {
-                group.entry(c).and_modify(|v| *v += 1).or_insert(1);
+    /// Per-question statistics over all the groups.
+    pub struct Report {
+        /// For each question, the number of groups in which anyone answered it.
+        anyone: [usize; 26],
+        /// For each question, the number of groups in which everyone answered it.
+        everyone: [usize; 26],
+        /// The number of groups of each size.
+        group_sizes: BTreeMap<usize, usize>,
+    }
+
+    impl Report {
+        pub fn new(input: &str) -> Self {
+            let mut report = Self {
+                anyone: [0; 26],
+                everyone: [0; 26],
+                group_sizes: BTreeMap::new(),
+            };
+            for group in parse_groups(input) {
+                let (union, intersection) = (group.union(), group.intersection());
+                for question in 0..26 {
+                    report.anyone[question] += union.contains(question as u8) as usize;
+                    report.everyone[question] += intersection.contains(question as u8) as usize;
+                }
+                *report.group_sizes.entry(group.0.len()).or_default() += 1;
             }
-            count += 1;
+            report
         }
-        sum += count_matching(&group, count);
 
-        sum
+        /// The questions with the highest or lowest `anyone` count, ignoring
+        /// questions that nobody answered.
+        fn extreme_questions(&self, most: bool) -> Vec<char> {
+            let counts = self.anyone.iter().copied().filter(|count| *count > 0);
+            let extreme = if most { counts.max() } else { counts.min() };
+            (b'a'..=b'z')
+                .zip(self.anyone.iter())
+                .filter(|(_, count)| Some(**count) == extreme)
+                .map(|(q, _)| q as char)
+                .collect()
+        }
+
+        pub fn to_json(&self) -> String {
+            let questions: Vec<_> = (b'a'..=b'z')
+                .enumerate()
+                .map(|(i, q)| {
+                    format!(
+                        r#""{}":{{"anyone":{},"everyone":{}}}"#,
+                        q as char, self.anyone[i], self.everyone[i]
+                    )
+                })
+                .collect();
+            let group_sizes: Vec<_> = self
+                .group_sizes
+                .iter()
+                .map(|(size, groups)| format!(r#""{}":{}"#, size, groups))
+                .collect();
+            let list = |questions: Vec<char>| {
+                let questions: Vec<_> = questions.iter().map(|q| format!(r#""{}""#, q)).collect();
+                questions.join(",")
+            };
+            format!(
+                r#"{{"questions":{{{}}},"group_sizes":{{{}}},"most_common":[{}],"least_common":[{}]}}"#,
+                questions.join(","),
+                group_sizes.join(","),
+                list(self.extreme_questions(true)),
+                list(self.extreme_questions(false)),
+            )
+        }
+    }
+
+    impl fmt::Display for Report {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            writeln!(f, "question  anyone  everyone")?;
+            for (i, q) in (b'a'..=b'z').enumerate() {
+                writeln!(
+                    f,
+                    "{:>8}  {:>6}  {:>8}",
+                    q as char, self.anyone[i], self.everyone[i]
+                )?;
+            }
+            writeln!(f)?;
+            writeln!(f, "size  groups")?;
+            for (size, groups) in &self.group_sizes {
+                writeln!(f, "{:>4}  {:>6}", size, groups)?;
+            }
+            writeln!(f)?;
+            let list = |questions: Vec<char>| {
+                let questions: Vec<_> = questions.iter().map(char::to_string).collect();
+                questions.join(", ")
+            };
+            writeln!(f, "most common: {}", list(self.extreme_questions(true)))?;
+            writeln!(f, "least common: {}", list(self.extreme_questions(false)))
+        }
     }
 }
 
 mod day_07 {
-    use std::collections::HashMap;
+    use std::collections::{HashMap, HashSet, VecDeque};
+    use std::fmt;
 
-    use crate::utils::split;
+    use crate::utils::{cyclic_components, BigUint};
 
+    /// A single rule, listing the bags directly inside a bag.
     #[derive(Debug)]
     struct Bag {
         color: String,
-        children: HashMap<String, usize>,
+        children: Vec<(String, usize)>,
     }
 
-    fn parse_bag(input: &str) -> Option<Bag> {
-        let (color, mut rest) = split(input, " bags contain ")?;
-        let mut bag = Bag {
-            color: color.to_owned(),
-            children: HashMap::new(),
-        };
+    impl fmt::Display for Bag {
+        /// Writes the rule in its canonical form.
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{} bags contain ", self.color)?;
+            if self.children.is_empty() {
+                return write!(f, "no other bags.");
+            }
+            for (i, (color, num)) in self.children.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                let plural = if *num == 1 { "" } else { "s" };
+                write!(f, "{} {} bag{}", num, color, plural)?;
+            }
+            write!(f, ".")
+        }
+    }
+
+    /// A malformed bag rule.
+    #[derive(Debug, PartialEq)]
+    pub struct ParseError {
+        line: usize,
+        column: usize,
+        message: String,
+    }
+
+    impl fmt::Display for ParseError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(
+                f,
+                "line {}, column {}: {}",
+                self.line, self.column, self.message
+            )
+        }
+    }
+
+    impl std::error::Error for ParseError {}
+
+    /// A parser for a single line of the rule grammar:
+    ///
+    /// ```text
+    /// rule     = color "bags" "contain" contents ["."]
+    /// contents = "no" "other" "bags" | item {"," item}
+    /// item     = number color "bags"
+    /// color    = word {word}
+    /// ```
+    ///
+    /// Words are runs of letters and digits separated by any amount of whitespace,
+    /// and `bag` and `bags` can be used interchangeably.
+    struct Parser<'a> {
+        line: usize,
+        input: &'a str,
+        pos: usize,
+    }
+
+    impl<'a> Parser<'a> {
+        fn error(&self, message: impl Into<String>) -> ParseError {
+            ParseError {
+                line: self.line,
+                column: self.input[..self.pos].chars().count() + 1,
+                message: message.into(),
+            }
+        }
+
+        fn rest(&self) -> &'a str {
+            &self.input[self.pos..]
+        }
+
+        fn skip_whitespace(&mut self) {
+            let rest = self.rest();
+            self.pos += rest.len() - rest.trim_start().len();
+        }
+
+        /// Looks at the next word, without consuming it.
+        fn peek_word(&self) -> &'a str {
+            let rest = self.rest();
+            let len = rest
+                .find(|c: char| !c.is_alphanumeric())
+                .unwrap_or(rest.len());
+            &rest[..len]
+        }
+
+        fn expect_word(&mut self, words: &[&str]) -> Result<(), ParseError> {
+            self.skip_whitespace();
+            let word = self.peek_word();
+            if !words.contains(&word) {
+                return Err(self.error(format!("expected `{}`", words[0])));
+            }
+            self.pos += word.len();
+            Ok(())
+        }
+
+        fn expect_bags(&mut self) -> Result<(), ParseError> {
+            self.expect_word(&["bags", "bag"])
+        }
+
+        fn number(&mut self) -> Result<usize, ParseError> {
+            self.skip_whitespace();
+            let rest = self.rest();
+            let len = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            let num = rest[..len]
+                .parse()
+                .map_err(|_| self.error("expected a number"))?;
+            self.pos += len;
+            Ok(num)
+        }
 
-        if rest != "no other bags." {
+        /// Parses all the words up to the next `bag` or `bags`.
+        fn color(&mut self) -> Result<String, ParseError> {
+            let mut words = vec![];
             loop {
-                let (num, rest2) = split(rest, " ")?;
-                let num = num.parse().ok()?;
-                let (color, mut rest2) = split(rest2, " bag")?;
-                bag.children.insert(color.to_owned(), num);
-                if let Some(rest3) = rest2.strip_prefix("s") {
-                    rest2 = rest3;
+                let start = self.pos;
+                self.skip_whitespace();
+                match self.peek_word() {
+                    "" | "bag" | "bags" => {
+                        self.pos = start;
+                        break;
+                    }
+                    word => {
+                        self.pos += word.len();
+                        words.push(word);
+                    }
                 }
-                if let Some(rest3) = rest2.strip_prefix(", ") {
-                    rest = rest3;
+            }
+            if words.is_empty() {
+                self.skip_whitespace();
+                return Err(self.error("expected a color"));
+            }
+            Ok(words.join(" "))
+        }
+
+        fn item(&mut self) -> Result<(String, usize), ParseError> {
+            let num = self.number()?;
+            let color = self.color()?;
+            self.expect_bags()?;
+            Ok((color, num))
+        }
+
+        fn rule(&mut self) -> Result<Bag, ParseError> {
+            let color = self.color()?;
+            self.expect_bags()?;
+            self.expect_word(&["contain"])?;
+
+            let mut children: Vec<(String, usize)> = vec![];
+            self.skip_whitespace();
+            if self.peek_word() == "no" {
+                self.expect_word(&["no"])?;
+                self.expect_word(&["other"])?;
+                self.expect_bags()?;
+            } else {
+                loop {
+                    let item_start = self.pos;
+                    let (color, num) = self.item()?;
+                    if children.iter().any(|(c, _)| *c == color) {
+                        self.pos = item_start;
+                        self.skip_whitespace();
+                        return Err(self.error(format!("duplicate bag `{}`", color)));
+                    }
+                    children.push((color, num));
+                    self.skip_whitespace();
+                    match self.rest().strip_prefix(',') {
+                        Some(_) => self.pos += 1,
+                        None => break,
+                    }
+                }
+            }
+
+            self.skip_whitespace();
+            if self.rest().starts_with('.') {
+                self.pos += 1;
+                self.skip_whitespace();
+            }
+            if !self.rest().is_empty() {
+                let expected = if children.is_empty() {
+                    "expected `.`"
                 } else {
-                    break;
+                    "expected `,` or `.`"
+                };
+                return Err(self.error(expected));
+            }
+
+            Ok(Bag { color, children })
+        }
+    }
+
+    /// Parses all the bag rules, skipping blank lines.
+    fn parse_bags(input: &str) -> Result<Vec<Bag>, ParseError> {
+        let mut bags: Vec<Bag> = vec![];
+        let mut lines = HashMap::new();
+        for (i, line) in input.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let mut parser = Parser {
+                line: i + 1,
+                input: line,
+                pos: 0,
+            };
+            let bag = parser.rule()?;
+            if let Some(previous) = lines.insert(bag.color.clone(), i + 1) {
+                parser.pos = 0;
+                parser.skip_whitespace();
+                return Err(parser.error(format!(
+                    "duplicate rule for `{}`, first defined on line {}",
+                    bag.color, previous
+                )));
+            }
+            bags.push(bag);
+        }
+        Ok(bags)
+    }
+
+    /// Parses the bag rules and writes them back in their canonical form.
+    pub fn canonicalize(input: &str) -> Result<String, ParseError> {
+        Ok(parse_bags(input)?
+            .iter()
+            .map(|bag| format!("{}\n", bag))
+            .collect())
+    }
+
+    /// A group of bags that all (transitively) contain each other.
+    #[derive(Debug)]
+    struct Cycle {
+        /// The colors of all the bags in the group, sorted.
+        colors: Vec<String>,
+        /// The colors along one of the cycles, starting and ending with the same
+        /// color.
+        example: Vec<String>,
+    }
+
+    /// The bag rules contain bags that (transitively) contain themselves.
+    #[derive(Debug)]
+    pub struct CycleError {
+        cycles: Vec<Cycle>,
+    }
+
+    impl fmt::Display for CycleError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "bag rules contain cycles: ")?;
+            for (i, cycle) in self.cycles.iter().enumerate() {
+                if i > 0 {
+                    write!(f, "; ")?;
                 }
+                write!(
+                    f,
+                    "{} (like {})",
+                    cycle.colors.join(", "),
+                    cycle.example.join(" -> ")
+                )?;
             }
+            Ok(())
         }
+    }
+
+    impl std::error::Error for CycleError {}
 
-        Some(bag)
+    /// The reasons why bag rules can not be used.
+    #[derive(Debug)]
+    pub enum RulesError {
+        Parse(ParseError),
+        Cycle(CycleError),
+        /// Counting the bags of the given color overflowed.
+        Overflow {
+            color: String,
+        },
     }
 
-    fn parse_bags(input: &str) -> HashMap<String, Bag> {
-        let mut bags = HashMap::new();
-        for line in input.lines() {
-            if let Some(bag) = parse_bag(line) {
-                bags.insert(bag.color.clone(), bag);
+    impl fmt::Display for RulesError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                RulesError::Parse(err) => err.fmt(f),
+                RulesError::Cycle(err) => err.fmt(f),
+                RulesError::Overflow { color } => {
+                    write!(f, "counting the `{}` bags overflowed", color)
+                }
             }
         }
-        bags
     }
 
-    fn does_contain(
-        bags: &HashMap<String, Bag>,
-        cache: &mut HashMap<String, bool>,
-        id: &str,
-        needle: &str,
-    ) -> bool {
-        if let Some(contains) = cache.get(id) {
-            return *contains;
+    impl std::error::Error for RulesError {}
+
+    impl From<ParseError> for RulesError {
+        fn from(err: ParseError) -> Self {
+            RulesError::Parse(err)
         }
-        if let Some(bag) = bags.get(id) {
-            let does_contain = bag
-                .children
-                .iter()
-                .any(|(id, _)| id == needle || does_contain(bags, cache, id, needle));
-            cache.insert(id.to_owned(), does_contain);
-            return does_contain;
+    }
+
+    impl From<CycleError> for RulesError {
+        fn from(err: CycleError) -> Self {
+            RulesError::Cycle(err)
         }
-        false
     }
 
-    pub fn a(input: &str) -> usize {
-        let bags = parse_bags(input);
-        let mut cache = HashMap::new();
-        bags.keys()
-            .filter(|color| does_contain(&bags, &mut cache, color.as_ref(), "shiny gold"))
-            .count()
+    /// Numbers that bags can be counted with.
+    pub trait BagCount: Clone + Default + PartialEq {
+        fn one() -> Self;
+
+        /// Adds `count` times `num` to `self`, or returns `None` on overflow.
+        fn add_product(&mut self, count: &Self, num: usize) -> Option<()>;
     }
 
-    fn total_count(
-        bags: &HashMap<String, Bag>,
-        cache: &mut HashMap<String, usize>,
-        id: &str,
-    ) -> usize {
-        if let Some(contains) = cache.get(id) {
-            return *contains;
+    impl BagCount for usize {
+        fn one() -> Self {
+            1
         }
-        if let Some(bag) = bags.get(id) {
-            let child_count = bag
-                .children
-                .iter()
-                .map(|(id, num)| *num * (1 + total_count(bags, cache, id)))
-                .sum();
-            cache.insert(id.to_owned(), child_count);
-            return child_count;
+
+        fn add_product(&mut self, count: &Self, num: usize) -> Option<()> {
+            *self = self.checked_add(count.checked_mul(num)?)?;
+            Some(())
         }
-        0
     }
 
-    pub fn b(input: &str) -> usize {
-        let bags = parse_bags(input);
-        let mut cache = HashMap::new();
-        total_count(&bags, &mut cache, "shiny gold")
+    impl BagCount for BigUint {
+        fn one() -> Self {
+            BigUint::from(1)
+        }
+
+        fn add_product(&mut self, count: &Self, num: usize) -> Option<()> {
+            self.add_mul(count, num);
+            Some(())
+        }
+    }
+
+    /// The bag rules, compiled into a graph with an integer id for each color.
+    pub struct BagRules {
+        /// The color of each bag id.
+        colors: Vec<String>,
+        ids: HashMap<String, u32>,
+        /// The bags directly inside each bag, sorted by color, with their counts.
+        children: Vec<Vec<(u32, usize)>>,
+        /// The bags directly containing each bag.
+        parents: Vec<Vec<u32>>,
+        /// All the bags, ordered so that every bag comes before all the bags it
+        /// contains. Bags that are part of a cycle are missing.
+        order: Vec<u32>,
+    }
+
+    impl BagRules {
+        fn new(bags: Vec<Bag>) -> Self {
+            let mut rules = Self {
+                colors: vec![],
+                ids: HashMap::new(),
+                children: vec![],
+                parents: vec![],
+                order: vec![],
+            };
+            for bag in bags {
+                let id = rules.intern(bag.color);
+                let children: Vec<_> = bag
+                    .children
+                    .into_iter()
+                    .map(|(color, num)| (rules.intern(color), num))
+                    .collect();
+                rules.children[id as usize] = children;
+            }
+            let colors = &rules.colors;
+            for (id, children) in rules.children.iter_mut().enumerate() {
+                children.sort_unstable_by_key(|(child, _)| &colors[*child as usize]);
+                for (child, _) in children.iter() {
+                    rules.parents[*child as usize].push(id as u32);
+                }
+            }
+            rules.order = rules.topological_order();
+            rules
+        }
+
+        fn intern(&mut self, color: String) -> u32 {
+            if let Some(id) = self.ids.get(&color) {
+                return *id;
+            }
+            let id = self.colors.len() as u32;
+            self.ids.insert(color.clone(), id);
+            self.colors.push(color);
+            self.children.push(vec![]);
+            self.parents.push(vec![]);
+            id
+        }
+
+        fn color(&self, id: u32) -> &str {
+            &self.colors[id as usize]
+        }
+
+        /// Parses the bag rules, making sure that no bag contains itself.
+        pub fn parse(input: &str) -> Result<Self, RulesError> {
+            let rules = Self::new(parse_bags(input)?);
+            if rules.order.len() != rules.colors.len() {
+                let cycles = rules.find_cycles();
+                return Err(CycleError { cycles }.into());
+            }
+            Ok(rules)
+        }
+
+        /// Orders the bags with Kahn's algorithm, leaving out bags that are part
+        /// of (or contained in) a cycle.
+        fn topological_order(&self) -> Vec<u32> {
+            let mut in_degrees: Vec<_> = self.parents.iter().map(Vec::len).collect();
+            let mut order: Vec<_> = (0..self.colors.len() as u32)
+                .filter(|id| in_degrees[*id as usize] == 0)
+                .collect();
+            let mut next = 0;
+            while let Some(id) = order.get(next).copied() {
+                next += 1;
+                for (child, _) in &self.children[id as usize] {
+                    in_degrees[*child as usize] -= 1;
+                    if in_degrees[*child as usize] == 0 {
+                        order.push(*child);
+                    }
+                }
+            }
+            order
+        }
+
+        /// Finds all the bags reachable from `start`, either by following the
+        /// containment edges forward or in `reverse`.
+        fn reachable(&self, start: u32, reverse: bool) -> Vec<bool> {
+            let mut seen = vec![false; self.colors.len()];
+            seen[start as usize] = true;
+            let mut queue = vec![start];
+            while let Some(id) = queue.pop() {
+                let mut visit = |next: u32| {
+                    if !seen[next as usize] {
+                        seen[next as usize] = true;
+                        queue.push(next);
+                    }
+                };
+                if reverse {
+                    self.parents[id as usize]
+                        .iter()
+                        .copied()
+                        .for_each(&mut visit);
+                } else {
+                    self.children[id as usize]
+                        .iter()
+                        .for_each(|(child, _)| visit(*child));
+                }
+            }
+            seen
+        }
+
+        /// Finds the groups of bags that contain each other, as the strongly
+        /// connected components of the rules.
+        ///
+        /// Listing every single cycle can take exponential time, so each group
+        /// comes with just one of its cycles as an example.
+        fn find_cycles(&self) -> Vec<Cycle> {
+            let children = |id: usize| self.children[id].iter().map(|(child, _)| *child as usize);
+            let components = cyclic_components(self.colors.len(), children);
+
+            let mut cycles: Vec<_> = components
+                .into_iter()
+                .map(|component| {
+                    let mut component: Vec<_> = component.into_iter().map(|id| id as u32).collect();
+                    component.sort_unstable_by_key(|id| self.color(*id));
+                    let example = self.shortest_cycle(component[0], &component);
+                    Cycle {
+                        colors: component
+                            .iter()
+                            .map(|id| self.color(*id).to_owned())
+                            .collect(),
+                        example: example
+                            .iter()
+                            .map(|id| self.color(*id).to_owned())
+                            .collect(),
+                    }
+                })
+                .collect();
+            cycles.sort_unstable_by(|a, b| a.colors.cmp(&b.colors));
+            cycles
+        }
+
+        /// Finds a shortest cycle from a bag back to itself with a breadth-first
+        /// search, staying within the bags of its component.
+        fn shortest_cycle(&self, start: u32, component: &[u32]) -> Vec<u32> {
+            let component: HashSet<_> = component.iter().copied().collect();
+            let mut previous = HashMap::new();
+            let mut queue = VecDeque::from(vec![start]);
+            while let Some(id) = queue.pop_front() {
+                for (child, _) in &self.children[id as usize] {
+                    if !component.contains(child) || previous.contains_key(child) {
+                        continue;
+                    }
+                    previous.insert(*child, id);
+                    if *child == start {
+                        let mut cycle = vec![start];
+                        let mut id = id;
+                        while id != start {
+                            cycle.push(id);
+                            id = previous[&id];
+                        }
+                        cycle.push(start);
+                        cycle.reverse();
+                        return cycle;
+                    }
+                    queue.push_back(*child);
+                }
+            }
+            vec![start]
+        }
+
+        /// Collects the colors of all the bags matching the filter, sorted.
+        fn sorted_colors(&self, filter: impl Fn(u32) -> bool) -> Vec<&str> {
+            let mut colors: Vec<_> = (0..self.colors.len() as u32)
+                .filter(|id| filter(*id))
+                .map(|id| self.color(id))
+                .collect();
+            colors.sort_unstable();
+            colors
+        }
+
+        /// All the bags that can eventually contain the given bag, sorted by color.
+        pub fn containers(&self, color: &str) -> Vec<&str> {
+            let id = match self.ids.get(color) {
+                Some(id) => *id,
+                None => return vec![],
+            };
+            let containers = self.reachable(id, true);
+            self.sorted_colors(|c| c != id && containers[c as usize])
+        }
+
+        /// All the bags the given bag has to contain, together with how many
+        /// of each there are in total, sorted by color.
+        pub fn contents<C: BagCount>(&self, color: &str) -> Result<Vec<(&str, C)>, RulesError> {
+            let id = match self.ids.get(color) {
+                Some(id) => *id,
+                None => return Ok(vec![]),
+            };
+            let mut counts = vec![C::default(); self.colors.len()];
+            counts[id as usize] = C::one();
+            for bag in self.order.iter().copied() {
+                let count = counts[bag as usize].clone();
+                if count == C::default() {
+                    continue;
+                }
+                for (child, num) in &self.children[bag as usize] {
+                    counts[*child as usize]
+                        .add_product(&count, *num)
+                        .ok_or_else(|| RulesError::Overflow {
+                            color: self.color(*child).to_owned(),
+                        })?;
+                }
+            }
+            counts[id as usize] = C::default();
+            let mut contents: Vec<_> = (0..self.colors.len() as u32)
+                .zip(counts)
+                .filter(|(_, count)| *count != C::default())
+                .map(|(c, count)| (self.color(c), count))
+                .collect();
+            contents.sort_unstable_by_key(|(color, _)| *color);
+            Ok(contents)
+        }
+
+        /// The total number of bags the given bag has to contain.
+        pub fn total<C: BagCount>(&self, color: &str) -> Result<C, RulesError> {
+            let mut total = C::default();
+            for (_, count) in self.contents::<C>(color)? {
+                total
+                    .add_product(&count, 1)
+                    .ok_or_else(|| RulesError::Overflow {
+                        color: color.to_owned(),
+                    })?;
+            }
+            Ok(total)
+        }
+
+        /// Follows the predecessors back from `to` to `from`.
+        fn chain(&self, predecessors: &[Option<u32>], from: u32, to: u32) -> Vec<&str> {
+            let mut chain = vec![to];
+            while let Some(id) = chain.last().copied().filter(|id| *id != from) {
+                chain.extend(predecessors[id as usize]);
+            }
+            chain.into_iter().rev().map(|id| self.color(id)).collect()
+        }
+
+        /// The chain of bags with the fewest steps from `from` down to `to`.
+        pub fn shortest_chain(&self, from: &str, to: &str) -> Option<Vec<&str>> {
+            let (from, to) = (*self.ids.get(from)?, *self.ids.get(to)?);
+            let mut predecessors = vec![None; self.colors.len()];
+            predecessors[from as usize] = Some(from);
+            let mut queue = VecDeque::new();
+            queue.push_back(from);
+            while let Some(id) = queue.pop_front() {
+                if id == to {
+                    return Some(self.chain(&predecessors, from, to));
+                }
+                for (child, _) in &self.children[id as usize] {
+                    if predecessors[*child as usize].is_none() {
+                        predecessors[*child as usize] = Some(id);
+                        queue.push_back(*child);
+                    }
+                }
+            }
+            None
+        }
+
+        /// The chain of bags with the most steps from `from` down to `to`.
+        pub fn longest_chain(&self, from: &str, to: &str) -> Option<Vec<&str>> {
+            let (from, to) = (*self.ids.get(from)?, *self.ids.get(to)?);
+            // the longest distance from `from` to each bag
+            let mut distances = vec![None; self.colors.len()];
+            let mut predecessors = vec![None; self.colors.len()];
+            distances[from as usize] = Some(0);
+            for id in self.order.iter().copied() {
+                let distance = match distances[id as usize] {
+                    Some(distance) => distance + 1,
+                    None => continue,
+                };
+                for (child, _) in &self.children[id as usize] {
+                    if distances[*child as usize] < Some(distance) {
+                        distances[*child as usize] = Some(distance);
+                        predecessors[*child as usize] = Some(id);
+                    }
+                }
+            }
+            distances[to as usize]?;
+            Some(self.chain(&predecessors, from, to))
+        }
+    }
+
+    pub fn a(input: &str) -> Result<usize, RulesError> {
+        Ok(BagRules::parse(input)?.containers("shiny gold").len())
+    }
+
+    pub fn b(input: &str) -> Result<usize, RulesError> {
+        BagRules::parse(input)?.total("shiny gold")
+    }
+
+    /// Generates a chain of bags that each contain the next one, with shiny gold
+    /// right in the middle.
+    pub fn long_chain(len: usize) -> String {
+        let color = |i: usize| {
+            if i == len / 2 {
+                "shiny gold".to_owned()
+            } else {
+                format!("shade{} hue", i)
+            }
+        };
+        (0..len)
+            .map(|i| format!("{} bags contain 1 {} bag.\n", color(i), color(i + 1)))
+            .collect()
+    }
+
+    /// Which part of the rule graph to look at.
+    #[derive(Clone, Copy, Debug)]
+    pub enum Subgraph<'a> {
+        All,
+        /// The given bag and all the bags it (transitively) contains.
+        ReachableFrom(&'a str),
+        /// The given bag and all the bags that (transitively) contain it.
+        LeadingTo(&'a str),
+    }
+
+    /// Exports the bag containment graph in the Graphviz DOT format, with the
+    /// number of contained bags as edge labels.
+    pub fn to_dot(input: &str, subgraph: Subgraph) -> Result<String, ParseError> {
+        let rules = BagRules::new(parse_bags(input)?);
+        let nodes = match subgraph {
+            Subgraph::All => vec![true; rules.colors.len()],
+            Subgraph::ReachableFrom(color) | Subgraph::LeadingTo(color) => {
+                match rules.ids.get(color) {
+                    Some(id) => rules.reachable(*id, matches!(subgraph, Subgraph::LeadingTo(_))),
+                    None => vec![false; rules.colors.len()],
+                }
+            }
+        };
+
+        let mut ids: Vec<_> = (0..rules.colors.len() as u32)
+            .filter(|id| nodes[*id as usize])
+            .collect();
+        ids.sort_unstable_by_key(|id| rules.color(*id));
+
+        let mut dot = String::from("digraph bags {\n");
+        for id in &ids {
+            dot.push_str(&format!("    {:?};\n", rules.color(*id)));
+        }
+        for id in &ids {
+            for (child, num) in &rules.children[*id as usize] {
+                if nodes[*child as usize] {
+                    dot.push_str(&format!(
+                        "    {:?} -> {:?} [label=\"{}\"];\n",
+                        rules.color(*id),
+                        rules.color(*child),
+                        num
+                    ));
+                }
+            }
+        }
+        dot.push_str("}\n");
+        Ok(dot)
     }
 }
 
 pub fn run() -> Result<(), Box<dyn std::error::Error>> {
+    // 0 -> 1 -> 2 -> 0, 3 -> 3, 4 -> 0
+    let edges = [vec![1], vec![2], vec![0], vec![3], vec![0]];
+    assert_eq!(
+        cyclic_components(edges.len(), |node| edges[node].clone()),
+        [vec![0, 1, 2], vec![3]]
+    );
+    assert_eq!(
+        records("\n\na b\r\nc\r\n\r\n\r\nd\n\n\ne\nf\n\n\n").collect::<Vec<_>>(),
+        vec![vec!["a b", "c"], vec!["d"], vec!["e", "f"]]
+    );
+    assert_eq!(
+        records("a\n\r\nb\r").collect::<Vec<_>>(),
+        vec![["a"], ["b"]]
+    );
+    assert_eq!(records("\n\n").count(), 0);
+
     assert_eq!(day_01::a(&example("01")?), 514579);
     assert_eq!(day_01::b(&example("01")?), 241861950);
 
@@ -472,22 +1588,390 @@ pub fn run() -> Result<(), Box<dyn std::error::Error>> {
     println!("day 04 a: {:?}", day_04::a(&input("04")?));
     println!("day 04 b: {:?}", day_04::b(&input("04")?));
 
-    assert_eq!(day_05::a(&example("05")?), 820);
-
-    println!("day 05 a: {:?}", day_05::a(&input("05")?));
-    println!("day 05 b: {:?}", day_05::b(&input("05")?));
+    let layout = day_05::PlaneLayout::default();
+    assert_eq!(day_05::a(&layout, &example("05")?)?, 820);
+    // swapping the partition characters mirrors every seat
+    let mirrored = day_05::PlaneLayout::new(128, 8, ['B', 'F', 'R', 'L']).unwrap();
+    assert_eq!(day_05::a(&mirrored, &example("05")?)?, 1023 - 119);
+    let compact = day_05::PlaneLayout::new(4, 4, ['U', 'D', '<', '>']).unwrap();
+    assert_eq!(day_05::a(&compact, "DU><\nUD<>")?, 10);
+    assert_eq!(
+        day_05::a(&layout, "BFFFBBFRRR\nFBFBBFFRLR\r")
+            .unwrap_err()
+            .to_string(),
+        r#"invalid boarding pass "FBFBBFFRLR\r" on line 2: invalid character '\r' at index 10"#
+    );
+    assert_eq!(
+        day_05::b(&layout, "FBFBBFFRLR\nFBFBBFFRL\nBFFFBBFRRR\nFBFBBFFRLRL")
+            .unwrap_err()
+            .to_string(),
+        "invalid boarding pass \"FBFBBFFRL\" on line 2: expected 10 characters, got 9\n\
+         invalid boarding pass \"FBFBBFFRLRL\" on line 4: superfluous partition 'L' at index 10"
+    );
+    // every possible boarding pass, in seat id order
+    let all_passes: String = (0..1024)
+        .map(|id| {
+            let bits = format!("{:010b}", id);
+            let (row, column) = bits.split_at(7);
+            let row = row.replace('0', "F").replace('1', "B");
+            let column = column.replace('0', "L").replace('1', "R");
+            format!("{}{}\n", row, column)
+        })
+        .collect();
+    let all_ids: Vec<usize> = (0..1024).collect();
+    assert_eq!(day_05::decode_seat_ids(&layout, &all_passes)?, all_ids);
+    assert_eq!(day_05::fast_seat_ids(&all_passes)?, all_ids);
+    assert_eq!(
+        day_05::fast_seat_ids("BFFFBBFRRR\nFFFBBBFRRR")?,
+        vec![567, 119]
+    );
+    for malformed in [
+        "BFFFBBFRRR\r\nFFFBBBFRRR\r\n",
+        "BFFFBBFRRR\n\n",
+        "BFFFBBFRRX\n",
+    ] {
+        assert_eq!(
+            day_05::fast_seat_ids(malformed).map_err(|err| err.to_string()),
+            day_05::decode_seat_ids(&layout, malformed).map_err(|err| err.to_string())
+        );
+    }
+    let input_05 = input("05")?;
+    assert_eq!(
+        day_05::decode_seat_ids(&layout, &input_05)?,
+        day_05::fast_seat_ids(&input_05)?
+    );
+    let map = day_05::SeatMap::new(&compact, "DU><\nUD<<\nUD><\nUD><\nUD>>\nUU<<")?;
+    assert_eq!(
+        map.to_string(),
+        "   0 #... 1/4\n   1 #@!# 3/4\n   2 ..#. 1/4\n   3 .... 0/4\n\
+         # occupied, . empty, ! duplicate, @ your seat\n\
+         your seat: 5 (row 1, column 1)\n\
+         missing: 1-3, 5, 8-9, 11-15 (back)\n\
+         duplicates: 6\n"
+    );
+
+    println!("day 05 a: {:?}", day_05::a(&layout, &input("05")?)?);
+    println!("day 05 b: {:?}", day_05::b(&layout, &input("05")?)?);
 
     assert_eq!(day_06::a(&example("06")?), 11);
     assert_eq!(day_06::b(&example("06")?), 6);
+    assert_eq!(day_06::query(&example("06")?, "odd".parse()?), 9);
+    assert_eq!(day_06::query(&example("06")?, "at-least-2".parse()?), 2);
+    assert_eq!(day_06::query(&example("06")?, "at-least-1".parse()?), 11);
+    assert!("at-least-".parse::<day_06::Query>().is_err());
+    let report = day_06::Report::new(&example("06")?).to_json();
+    assert!(report.starts_with(
+        r#"{"questions":{"a":{"anyone":4,"everyone":3},"b":{"anyone":4,"everyone":2},"c":{"anyone":3,"everyone":1},"d":{"anyone":0,"everyone":0},"#
+    ));
+    assert!(report.ends_with(
+        r#""group_sizes":{"1":2,"2":1,"3":1,"4":1},"most_common":["a","b"],"least_common":["c"]}"#
+    ));
 
     println!("day 06 a: {:?}", day_06::a(&input("06")?));
     println!("day 06 b: {:?}", day_06::b(&input("06")?));
 
-    assert_eq!(day_07::a(&example("07")?), 4);
-    assert_eq!(day_07::b(&example("07b")?), 126);
-
-    println!("day 07 a: {:?}", day_07::a(&input("07")?));
-    println!("day 07 b: {:?}", day_07::b(&input("07")?));
+    assert_eq!(day_07::a(&example("07")?)?, 4);
+    assert_eq!(day_07::b(&example("07b")?)?, 126);
+    assert_eq!(
+        day_07::canonicalize(&example("07")?)?,
+        format!("{}\n", example("07")?)
+    );
+    assert_eq!(
+        day_07::canonicalize(
+            "light  red bags contain 1 bright white bags ,2 muted yellow bag\n\n\
+             faded blue bag contain no other bag"
+        )?,
+        "light red bags contain 1 bright white bag, 2 muted yellow bags.\n\
+         faded blue bags contain no other bags.\n"
+    );
+    let parse_error = |input| day_07::canonicalize(input).unwrap_err().to_string();
+    assert_eq!(
+        parse_error(
+            "light red bags contain 1 bright white bag.\nfaded blue bags contains no other bags."
+        ),
+        "line 2, column 17: expected `contain`"
+    );
+    assert_eq!(
+        parse_error("light red bags contain 1 bright white bag 2 muted yellow bags."),
+        "line 1, column 43: expected `,` or `.`"
+    );
+    assert_eq!(
+        parse_error("light red bags contain one bright white bag."),
+        "line 1, column 24: expected a number"
+    );
+    assert_eq!(
+        parse_error("light red bags contain 1 bag."),
+        "line 1, column 26: expected a color"
+    );
+    assert_eq!(
+        parse_error("light red bags contain 1 dark red bag, 2 dark red bags."),
+        "line 1, column 40: duplicate bag `dark red`"
+    );
+    assert_eq!(
+        parse_error("dark red bags contain no other bags.\n\ndark red bags contain no other bags."),
+        "line 3, column 1: duplicate rule for `dark red`, first defined on line 1"
+    );
+    assert_eq!(
+        parse_error("faded blue bags contain no other bags, 1 dark red bag."),
+        "line 1, column 38: expected `.`"
+    );
+    let cyclic = "a b bags contain 1 c d bag.\n\
+                  c d bags contain 2 a b bags, 1 e f bag.\n\
+                  e f bags contain 1 e f bag.\n\
+                  g h bags contain 1 a b bag.";
+    assert_eq!(
+        day_07::b(cyclic).unwrap_err().to_string(),
+        "bag rules contain cycles: a b, c d (like a b -> c d -> a b); e f (like e f -> e f)"
+    );
+    let diamond = "a a bags contain 1 b b bag, 1 c c bag.\n\
+                   b b bags contain 1 d d bag.\n\
+                   c c bags contain 1 d d bag.\n\
+                   d d bags contain 1 a a bag.\n\
+                   e e bags contain 1 f f bag.\n\
+                   f f bags contain 1 e e bag, 1 g g bag.\n\
+                   g g bags contain 1 f f bag.";
+    assert_eq!(
+        day_07::b(diamond).unwrap_err().to_string(),
+        "bag rules contain cycles: a a, b b, c c, d d (like a a -> b b -> d d -> a a); \
+         e e, f f, g g (like e e -> f f -> e e)"
+    );
+    assert!(day_07::a(cyclic).is_err());
+    let rules = day_07::BagRules::parse(&example("07")?)?;
+    assert_eq!(
+        rules.containers("shiny gold"),
+        ["bright white", "dark orange", "light red", "muted yellow"]
+    );
+    assert_eq!(
+        rules.contents::<usize>("shiny gold")?,
+        [
+            ("dark olive", 1),
+            ("dotted black", 16),
+            ("faded blue", 13),
+            ("vibrant plum", 2)
+        ]
+    );
+    assert_eq!(
+        rules.shortest_chain("light red", "faded blue"),
+        Some(vec!["light red", "muted yellow", "faded blue"])
+    );
+    assert_eq!(
+        rules
+            .longest_chain("light red", "faded blue")
+            .map(|c| c.len()),
+        Some(5)
+    );
+    assert_eq!(rules.shortest_chain("faded blue", "light red"), None);
+    assert_eq!(
+        rules.longest_chain("shiny gold", "shiny gold"),
+        Some(vec!["shiny gold"])
+    );
+    let chain = day_07::long_chain(4000);
+    assert_eq!(day_07::a(&chain)?, 2000);
+    assert_eq!(day_07::b(&chain)?, 2000);
+    // a bag with a thousand times as many bags on each of a hundred levels
+    let adversarial: String = (0..100)
+        .map(|i| format!("level{} bags contain 1000 level{} bags.\n", i, i + 1))
+        .collect();
+    assert_eq!(
+        day_07::BagRules::parse(&adversarial)?
+            .total::<usize>("level0")
+            .unwrap_err()
+            .to_string(),
+        "counting the `level7` bags overflowed"
+    );
+    assert_eq!(
+        day_07::BagRules::parse(&adversarial)?
+            .total::<BigUint>("level0")?
+            .to_string(),
+        format!("1{}000", "001".repeat(99))
+    );
+    assert_eq!(
+        day_07::BagRules::parse(&example("07b")?)?
+            .total::<BigUint>("shiny gold")?
+            .to_string(),
+        "126"
+    );
+    let rules = day_07::BagRules::parse(&example("07b")?)?;
+    assert_eq!(
+        rules.shortest_chain("shiny gold", "dark violet"),
+        rules.longest_chain("shiny gold", "dark violet")
+    );
+    assert_eq!(
+        day_07::to_dot(
+            &example("07b")?,
+            day_07::Subgraph::ReachableFrom("dark blue")
+        )?,
+        r#"digraph bags {
+    "dark blue";
+    "dark violet";
+    "dark blue" -> "dark violet" [label="2"];
+}
+"#
+    );
+    assert_eq!(
+        day_07::to_dot(&example("07")?, day_07::Subgraph::LeadingTo("bright white"))?,
+        r#"digraph bags {
+    "bright white";
+    "dark orange";
+    "light red";
+    "dark orange" -> "bright white" [label="3"];
+    "light red" -> "bright white" [label="1"];
+}
+"#
+    );
+    assert_eq!(
+        day_07::to_dot(&example("07")?, day_07::Subgraph::All)?
+            .matches(" -> ")
+            .count(),
+        13
+    );
+
+    println!("day 07 a: {:?}", day_07::a(&input("07")?)?);
+    println!("day 07 b: {:?}", day_07::b(&input("07")?)?);
 
     Ok(())
 }
+
+/// Parses the optional `[<rows> <columns> <FBLR>] [path]` arguments of the day 5 tools.
+fn layout_args(
+    args: &[String],
+) -> Result<(day_05::PlaneLayout, Option<&String>), Box<dyn std::error::Error>> {
+    let (rows, columns, chars, path) = match args {
+        [] | [_] => return Ok((day_05::PlaneLayout::default(), args.first())),
+        [rows, columns, chars] => (rows, columns, chars, None),
+        [rows, columns, chars, path] => (rows, columns, chars, Some(path)),
+        _ => return Err("expected `[<rows> <columns> <FBLR>] [path]`".into()),
+    };
+    let chars = match *chars.chars().collect::<Vec<_>>() {
+        [front, back, left, right] => [front, back, left, right],
+        _ => return Err(format!("expected 4 partition characters, got {:?}", chars).into()),
+    };
+    let layout = day_05::PlaneLayout::new(rows.parse()?, columns.parse()?, chars)
+        .ok_or("rows and columns need to be powers of two and the partition characters distinct")?;
+    Ok((layout, path))
+}
+
+/// Runs one of the additional tools of the given day.
+///
+/// Returns `false` if this week has no such day or tool.
+pub fn tool(day: &str, args: &[String]) -> Result<bool, Box<dyn std::error::Error>> {
+    match (day, args.first().map(String::as_str)) {
+        ("05", Some("seats")) => {
+            let (layout, path) = layout_args(&args[1..])?;
+            let input = read_input(day, path)?;
+            print!("{}", day_05::SeatMap::new(&layout, &input)?);
+        }
+        ("05", Some("bench")) => {
+            const ROUNDS: u32 = 10_000;
+            let (layout, path) = layout_args(&args[1..])?;
+            let input = read_input(day, path)?;
+
+            let start = Instant::now();
+            for _ in 0..ROUNDS {
+                black_box(day_05::decode_seat_ids(&layout, black_box(&input))?);
+            }
+            println!("range halving: {:?}", start.elapsed() / ROUNDS);
+
+            // the bit parser only understands the default layout
+            if layout != day_05::PlaneLayout::default() {
+                return Ok(true);
+            }
+            let start = Instant::now();
+            for _ in 0..ROUNDS {
+                black_box(day_05::fast_seat_ids(black_box(&input))?);
+            }
+            println!("bit parsing:   {:?}", start.elapsed() / ROUNDS);
+        }
+        ("06", Some("query")) => {
+            let query: day_06::Query = args.get(1).ok_or("missing query")?.parse()?;
+            let input = read_input(day, args.get(2))?;
+            println!("{}", day_06::query(&input, query));
+        }
+        ("06", Some("report")) => {
+            let input = read_input(day, args.get(2))?;
+            let report = day_06::Report::new(&input);
+            match args.get(1).map(String::as_str) {
+                Some("table") => print!("{}", report),
+                Some("json") => println!("{}", report.to_json()),
+                _ => return Err("expected `table` or `json` format".into()),
+            }
+        }
+        ("07", Some("dot")) => {
+            let (subgraph, path) = match args.get(1).map(String::as_str) {
+                Some("all") => (day_07::Subgraph::All, args.get(2)),
+                Some(direction @ "from") | Some(direction @ "to") => {
+                    let color = args.get(2).ok_or("missing bag color")?;
+                    let subgraph = if direction == "from" {
+                        day_07::Subgraph::ReachableFrom(color)
+                    } else {
+                        day_07::Subgraph::LeadingTo(color)
+                    };
+                    (subgraph, args.get(3))
+                }
+                _ => return Err("expected `all`, `from <color>` or `to <color>`".into()),
+            };
+            let input = read_input(day, path)?;
+            print!("{}", day_07::to_dot(&input, subgraph)?);
+        }
+        ("07", Some("containers")) | ("07", Some("contents")) => {
+            let color = args.get(1).ok_or("missing bag color")?;
+            let rules = day_07::BagRules::parse(&read_input(day, args.get(2))?)?;
+            let mut out = std::io::stdout().lock();
+            if args[0] == "containers" {
+                for container in rules.containers(color) {
+                    writeln!(out, "{}", container)?;
+                }
+            } else {
+                for (content, num) in rules.contents::<BigUint>(color)? {
+                    writeln!(out, "{} {}", num, content)?;
+                }
+            }
+        }
+        ("07", Some("total")) => {
+            let color = args.get(2).ok_or("missing bag color")?;
+            let rules = day_07::BagRules::parse(&read_input(day, args.get(3))?)?;
+            match args.get(1).map(String::as_str) {
+                Some("checked") => println!("{}", rules.total::<usize>(color)?),
+                Some("exact") => println!("{}", rules.total::<BigUint>(color)?),
+                _ => return Err("expected `checked` or `exact`".into()),
+            }
+        }
+        ("07", Some("chain")) => {
+            let (from, to) = match (args.get(2), args.get(3)) {
+                (Some(from), Some(to)) => (from, to),
+                _ => return Err("missing `<from>` and `<to>` bag colors".into()),
+            };
+            let rules = day_07::BagRules::parse(&read_input(day, args.get(4))?)?;
+            let chain = match args.get(1).map(String::as_str) {
+                Some("shortest") => rules.shortest_chain(from, to),
+                Some("longest") => rules.longest_chain(from, to),
+                _ => return Err("expected `shortest` or `longest`".into()),
+            };
+            match chain {
+                Some(chain) => println!("{}", chain.join(" -> ")),
+                None => println!("{} can not contain {}", from, to),
+            }
+        }
+        ("07", Some("bench")) => {
+            let len = args.get(1).map_or(Ok(200_000), |len| len.parse())?;
+            let chain = day_07::long_chain(len);
+
+            let start = Instant::now();
+            let containers = day_07::a(black_box(&chain))?;
+            println!("part a: {:?}", start.elapsed());
+
+            let start = Instant::now();
+            let contents = day_07::b(black_box(&chain))?;
+            println!("part b: {:?}", start.elapsed());
+
+            if (containers, contents) != (len / 2, len - len / 2) {
+                return Err(format!("wrong counts {} and {}", containers, contents).into());
+            }
+        }
+        ("07", Some("fmt")) => {
+            print!("{}", day_07::canonicalize(&read_input(day, args.get(1))?)?);
+        }
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
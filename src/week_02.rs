@@ -1,83 +1,560 @@
-use crate::utils::{example, input};
+use std::io::Write;
+use std::time::Instant;
+
+use crate::utils::{example, input, open_input, read_input, Rng};
 
 mod day_08 {
-    use crate::utils::split;
+    use std::collections::HashMap;
+    use std::fmt;
+    use std::io::{self, BufRead, Write};
+
+    use crate::utils::{cyclic_components, split, Rng};
+
+    /// The names of the registers, the first one being the accumulator.
+    const REGISTERS: [&str; 4] = ["acc", "x", "y", "z"];
+    const ACC: usize = 0;
+
+    #[derive(Clone, Copy, Debug)]
+    enum Operand {
+        Value(isize),
+        Register(usize),
+    }
 
     #[derive(Debug)]
     enum Instruction {
         Nop(isize),
         Acc(isize),
         Jmp(isize),
+        Set(usize, Operand),
+        Add(usize, Operand),
+        Mul(usize, Operand),
+        /// Jumps if the register is zero.
+        Jz(usize, isize),
+        /// Jumps if the register is not zero.
+        Jnz(usize, isize),
+        Out(Operand),
+        Hlt,
     }
 
     #[derive(Debug, Default)]
     struct ProcessorState {
         instruction_pointer: usize,
-        accumulator: isize,
+        registers: [isize; REGISTERS.len()],
+        output: Vec<isize>,
+        /// The number of instructions executed so far.
+        steps: usize,
     }
 
     impl ProcessorState {
-        fn execute_instruction(&mut self, instr: &Instruction) -> isize {
-            match instr {
+        fn accumulator(&self) -> isize {
+            self.registers[ACC]
+        }
+
+        fn value(&self, operand: Operand) -> isize {
+            match operand {
+                Operand::Value(value) => value,
+                Operand::Register(reg) => self.registers[reg],
+            }
+        }
+
+        /// Executes a single instruction, returning the offset to the next one,
+        /// or `None` if the processor halts.
+        ///
+        /// Arithmetic wraps around, like it would on a real processor.
+        fn execute_instruction(&mut self, instr: &Instruction) -> Option<isize> {
+            Some(match instr {
                 Instruction::Nop(_) => 1,
                 Instruction::Acc(arg) => {
-                    self.accumulator += *arg;
+                    self.registers[ACC] = self.registers[ACC].wrapping_add(*arg);
                     1
                 }
                 Instruction::Jmp(arg) => *arg,
+                Instruction::Set(reg, op) => {
+                    self.registers[*reg] = self.value(*op);
+                    1
+                }
+                Instruction::Add(reg, op) => {
+                    self.registers[*reg] = self.registers[*reg].wrapping_add(self.value(*op));
+                    1
+                }
+                Instruction::Mul(reg, op) => {
+                    self.registers[*reg] = self.registers[*reg].wrapping_mul(self.value(*op));
+                    1
+                }
+                Instruction::Jz(reg, arg) => {
+                    if self.registers[*reg] == 0 {
+                        *arg
+                    } else {
+                        1
+                    }
+                }
+                Instruction::Jnz(reg, arg) => {
+                    if self.registers[*reg] != 0 {
+                        *arg
+                    } else {
+                        1
+                    }
+                }
+                Instruction::Out(op) => {
+                    self.output.push(self.value(*op));
+                    1
+                }
+                Instruction::Hlt => return None,
+            })
+        }
+
+        /// Executes the instruction at the instruction pointer, returning the
+        /// outcome if that ends the program.
+        ///
+        /// A jump out of bounds leaves the instruction pointer at the jump.
+        fn step(&mut self, instructions: &[Instruction]) -> Option<Outcome> {
+            let ip = self.instruction_pointer;
+            let instr = instructions.get(ip)?;
+            self.steps += 1;
+            let offset = match self.execute_instruction(instr) {
+                Some(offset) => offset,
+                None => return Some(Outcome::Halted),
+            };
+            self.instruction_pointer = match jump_target(instructions.len(), ip, offset) {
+                Some(target) => target,
+                None => {
+                    let target = (ip as isize).saturating_add(offset);
+                    return Some(Outcome::OutOfBounds { ip, target });
+                }
+            };
+            if self.instruction_pointer == instructions.len() {
+                return Some(Outcome::Terminated);
             }
+            None
         }
 
-        fn run(&mut self, instructions: &[Instruction]) -> bool {
-            let mut executed: Vec<_> = instructions.iter().map(|i| (i, false)).collect();
+        /// Runs the program until it ends, or until it would execute an
+        /// instruction a second time.
+        ///
+        /// The loop detection is only sound for boot code, where control flow
+        /// does not depend on the registers. Use `run_for` for everything else.
+        fn run(&mut self, instructions: &[Instruction]) -> Outcome {
+            let mut executed = vec![false; instructions.len()];
 
-            while let Some((instr, executed)) = executed.get_mut(self.instruction_pointer) {
-                if *executed {
-                    break;
+            loop {
+                let ip = self.instruction_pointer;
+                match executed.get_mut(ip) {
+                    Some(true) => return Outcome::LoopDetected { ip },
+                    Some(executed) => *executed = true,
+                    // only an empty program can start out of bounds
+                    None => return Outcome::Terminated,
                 }
-                *executed = true;
-                let offset = self.execute_instruction(instr);
-                if offset < 0 {
-                    if let Some(ip) = self.instruction_pointer.checked_sub(-offset as usize) {
-                        self.instruction_pointer = ip;
-                    } else {
-                        return false;
-                    }
-                } else {
-                    self.instruction_pointer += offset as usize;
+                if let Some(outcome) = self.step(instructions) {
+                    return outcome;
                 }
-                if self.instruction_pointer == instructions.len() {
-                    return true;
+            }
+        }
+
+        /// Runs the program for at most `max_steps` instructions, returning the
+        /// outcome if it ended in time.
+        fn run_for(&mut self, instructions: &[Instruction], max_steps: usize) -> Option<Outcome> {
+            if instructions.is_empty() {
+                return Some(Outcome::Terminated);
+            }
+            for _ in 0..max_steps {
+                if let Some(outcome) = self.step(instructions) {
+                    return Some(outcome);
                 }
             }
-            false
+            None
+        }
+    }
+
+    /// An instruction with its jump target resolved to an absolute instruction
+    /// pointer, which is `OUT_OF_BOUNDS` for jumps outside of the program.
+    #[derive(Clone, Copy)]
+    enum Op {
+        Nop,
+        Acc(isize),
+        Jmp(usize),
+        Set(usize, Operand),
+        Add(usize, Operand),
+        Mul(usize, Operand),
+        Jz(usize, usize),
+        Jnz(usize, usize),
+        Out(Operand),
+        Hlt,
+    }
+
+    const OUT_OF_BOUNDS: usize = usize::MAX;
+
+    fn compile(len: usize, ip: usize, instr: &Instruction) -> Op {
+        let target = |offset| jump_target(len, ip, offset).unwrap_or(OUT_OF_BOUNDS);
+        match *instr {
+            Instruction::Nop(_) => Op::Nop,
+            Instruction::Acc(arg) => Op::Acc(arg),
+            Instruction::Jmp(arg) => Op::Jmp(target(arg)),
+            Instruction::Set(reg, op) => Op::Set(reg, op),
+            Instruction::Add(reg, op) => Op::Add(reg, op),
+            Instruction::Mul(reg, op) => Op::Mul(reg, op),
+            Instruction::Jz(reg, arg) => Op::Jz(reg, target(arg)),
+            Instruction::Jnz(reg, arg) => Op::Jnz(reg, target(arg)),
+            Instruction::Out(op) => Op::Out(op),
+            Instruction::Hlt => Op::Hlt,
         }
     }
 
-    fn parse_instructions(input: &str) -> Vec<Instruction> {
-        input
-            .lines()
-            .flat_map(|line| {
-                let (instr, arg) = split(line, " ")?;
-                let arg = arg.parse().ok()?;
-                Some(match instr {
-                    "nop" => Instruction::Nop(arg),
-                    "acc" => Instruction::Acc(arg),
-                    "jmp" => Instruction::Jmp(arg),
-                    _ => {
-                        return None;
+    /// Which implementation runs a program.
+    #[derive(Clone, Copy)]
+    pub enum Engine {
+        Interpreter,
+        Compiled,
+    }
+
+    /// Boot code compiled for running it many times, along with the state of
+    /// its last run.
+    ///
+    /// Running it does not allocate, as the set of executed instructions and
+    /// the output are reused between runs.
+    pub struct Program {
+        instructions: Vec<Instruction>,
+        ops: Vec<Op>,
+        /// One bit per instruction.
+        executed: Vec<u64>,
+        registers: [isize; REGISTERS.len()],
+        output: Vec<isize>,
+        steps: usize,
+    }
+
+    impl Program {
+        pub fn new(input: &str) -> Result<Self, AssembleError> {
+            let instructions = assemble(input)?;
+            let len = instructions.len();
+            let ops = instructions
+                .iter()
+                .enumerate()
+                .map(|(ip, instr)| compile(len, ip, instr))
+                .collect();
+            Ok(Program {
+                instructions,
+                ops,
+                executed: vec![0; len.div_ceil(64)],
+                registers: Default::default(),
+                output: vec![],
+                steps: 0,
+            })
+        }
+
+        pub fn accumulator(&self) -> isize {
+            self.registers[ACC]
+        }
+
+        fn value(&self, operand: Operand) -> isize {
+            match operand {
+                Operand::Value(value) => value,
+                Operand::Register(reg) => self.registers[reg],
+            }
+        }
+
+        /// Swaps a `nop` with a `jmp` or the other way around.
+        fn swap(&mut self, ip: usize) {
+            swap_nop_jmp(&mut self.instructions[ip]);
+            self.ops[ip] = compile(self.ops.len(), ip, &self.instructions[ip]);
+        }
+
+        /// Runs the program from the start like `ProcessorState::run`.
+        pub fn run(&mut self) -> Outcome {
+            let len = self.ops.len();
+            self.executed.fill(0);
+            self.registers = Default::default();
+            self.output.clear();
+            self.steps = 0;
+
+            let mut ip = 0;
+            while ip < len {
+                let (word, bit) = (ip / 64, 1 << (ip % 64));
+                if self.executed[word] & bit != 0 {
+                    return Outcome::LoopDetected { ip };
+                }
+                self.executed[word] |= bit;
+                self.steps += 1;
+
+                let next = match self.ops[ip] {
+                    Op::Nop => ip + 1,
+                    Op::Acc(arg) => {
+                        self.registers[ACC] = self.registers[ACC].wrapping_add(arg);
+                        ip + 1
                     }
+                    Op::Jmp(target) => target,
+                    Op::Set(reg, op) => {
+                        self.registers[reg] = self.value(op);
+                        ip + 1
+                    }
+                    Op::Add(reg, op) => {
+                        self.registers[reg] = self.registers[reg].wrapping_add(self.value(op));
+                        ip + 1
+                    }
+                    Op::Mul(reg, op) => {
+                        self.registers[reg] = self.registers[reg].wrapping_mul(self.value(op));
+                        ip + 1
+                    }
+                    Op::Jz(reg, target) if self.registers[reg] == 0 => target,
+                    Op::Jnz(reg, target) if self.registers[reg] != 0 => target,
+                    Op::Jz(..) | Op::Jnz(..) => ip + 1,
+                    Op::Out(op) => {
+                        self.output.push(self.value(op));
+                        ip + 1
+                    }
+                    Op::Hlt => return Outcome::Halted,
+                };
+                if next == OUT_OF_BOUNDS {
+                    let offset = match self.instructions[ip] {
+                        Instruction::Jmp(arg)
+                        | Instruction::Jz(_, arg)
+                        | Instruction::Jnz(_, arg) => arg,
+                        _ => 1,
+                    };
+                    let target = (ip as isize).saturating_add(offset);
+                    return Outcome::OutOfBounds { ip, target };
+                }
+                ip = next;
+            }
+            Outcome::Terminated
+        }
+
+        /// Repairs the program by trying to swap every single `nop` and `jmp`,
+        /// returning the instructions for which that makes it terminate.
+        pub fn brute_force_repairs(&mut self, engine: Engine) -> Vec<usize> {
+            let mut repairs = vec![];
+            for ip in 0..self.instructions.len() {
+                if !matches!(
+                    self.instructions[ip],
+                    Instruction::Nop(_) | Instruction::Jmp(_)
+                ) {
+                    continue;
+                }
+                self.swap(ip);
+                let outcome = match engine {
+                    Engine::Interpreter => ProcessorState::default().run(&self.instructions),
+                    Engine::Compiled => self.run(),
+                };
+                if let Outcome::Terminated | Outcome::Halted = outcome {
+                    repairs.push(ip);
+                }
+                self.swap(ip);
+            }
+            repairs
+        }
+    }
+
+    /// Generates random boot code, with jumps anywhere in the program.
+    pub fn generate(len: usize, seed: u64) -> String {
+        let mut rng = Rng::new(seed);
+        let mut program = String::new();
+        for ip in 0..len {
+            let offset = rng.below(len as u64 + 1) as isize - ip as isize;
+            let line = match rng.below(4) {
+                0 => format!("nop {:+}\n", offset),
+                1 => format!("jmp {:+}\n", offset),
+                _ => format!("acc {:+}\n", rng.below(100) as isize - 50),
+            };
+            program.push_str(&line);
+        }
+        program
+    }
+
+    /// How running a program ended.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum Outcome {
+        /// The program continued right after its last instruction.
+        Terminated,
+        /// The program executed `hlt`.
+        Halted,
+        /// The instruction at `ip` was about to be executed a second time.
+        LoopDetected { ip: usize },
+        /// The instruction at `ip` jumped to `target`, outside of the program.
+        OutOfBounds { ip: usize, target: isize },
+    }
+
+    impl fmt::Display for Outcome {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Outcome::Terminated => write!(f, "program terminated"),
+                Outcome::Halted => write!(f, "program halted"),
+                Outcome::LoopDetected { ip } => write!(f, "loop detected at ip {}", ip),
+                Outcome::OutOfBounds { ip, target } => {
+                    write!(f, "ip {} jumped out of bounds to {}", ip, target)
+                }
+            }
+        }
+    }
+
+    fn parse_register(input: &str) -> Option<usize> {
+        REGISTERS.iter().position(|reg| *reg == input)
+    }
+
+    /// A program line that can not be assembled.
+    #[derive(Debug, PartialEq)]
+    pub struct AssembleError {
+        line: usize,
+        message: String,
+    }
+
+    impl fmt::Display for AssembleError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "line {}: {}", self.line, self.message)
+        }
+    }
+
+    impl std::error::Error for AssembleError {}
+
+    fn is_label(name: &str) -> bool {
+        let mut chars = name.chars();
+        chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+            && parse_register(name).is_none()
+    }
+
+    /// Splits a line into its label definitions and its instruction, removing
+    /// any comment.
+    fn split_labels(line: &str) -> (Vec<&str>, &str) {
+        let mut rest = line.split('#').next().unwrap_or("").trim();
+        let mut labels = vec![];
+        while let Some((label, after)) = split(rest, ":") {
+            labels.push(label.trim());
+            rest = after.trim();
+        }
+        (labels, rest)
+    }
+
+    /// Assembles a program into instructions.
+    ///
+    /// Besides the instructions themselves, lines can contain `# comments`, and
+    /// any number of `label:` definitions. Jump offsets can name a label instead,
+    /// which includes a label right after the last instruction.
+    fn assemble(input: &str) -> Result<Vec<Instruction>, AssembleError> {
+        // the first pass resolves all the labels to their instruction pointers
+        let mut labels = HashMap::new();
+        let mut lines = vec![];
+        for (i, line) in input.lines().enumerate() {
+            let (defined, instr) = split_labels(line);
+            for label in defined {
+                let error = |message| AssembleError {
+                    line: i + 1,
+                    message,
+                };
+                if !is_label(label) {
+                    return Err(error(format!("invalid label `{}`", label)));
+                }
+                if labels.insert(label, lines.len()).is_some() {
+                    return Err(error(format!("duplicate label `{}`", label)));
+                }
+            }
+            if !instr.is_empty() {
+                lines.push((i + 1, instr));
+            }
+        }
+
+        lines
+            .iter()
+            .enumerate()
+            .map(|(ip, (line, instr))| {
+                assemble_instruction(instr, ip, &labels).map_err(|message| AssembleError {
+                    line: *line,
+                    message,
                 })
             })
             .collect()
     }
 
-    pub fn a(input: &str) -> isize {
-        let instructions: Vec<_> = parse_instructions(input);
-        let mut state = ProcessorState::default();
+    fn assemble_instruction(
+        input: &str,
+        ip: usize,
+        labels: &HashMap<&str, usize>,
+    ) -> Result<Instruction, String> {
+        let mut words = input.split_whitespace();
+        let opcode = words.next().unwrap_or("");
+        let operands: Vec<_> = words.collect();
+
+        let register =
+            |name: &str| parse_register(name).ok_or_else(|| format!("invalid register `{}`", name));
+        let operand = |op: &str| match parse_register(op) {
+            Some(reg) => Ok(Operand::Register(reg)),
+            None => op
+                .parse()
+                .map(Operand::Value)
+                .map_err(|_| format!("invalid operand `{}`", op)),
+        };
+        let offset = |op: &str| match labels.get(op) {
+            Some(target) => Ok(*target as isize - ip as isize),
+            None if is_label(op) => Err(format!("unknown label `{}`", op)),
+            None => op.parse().map_err(|_| format!("invalid offset `{}`", op)),
+        };
+
+        let expected = match opcode {
+            "nop" | "acc" | "jmp" | "out" => 1,
+            "set" | "add" | "mul" | "jz" | "jnz" => 2,
+            "hlt" => 0,
+            _ => return Err(format!("unknown opcode `{}`", opcode)),
+        };
+        if operands.len() != expected {
+            return Err(format!(
+                "`{}` expects {} operands, got {}",
+                opcode,
+                expected,
+                operands.len()
+            ));
+        }
+
+        Ok(match opcode {
+            "nop" => Instruction::Nop(offset(operands[0])?),
+            "acc" => Instruction::Acc(
+                operands[0]
+                    .parse()
+                    .map_err(|_| format!("invalid value `{}`", operands[0]))?,
+            ),
+            "jmp" => Instruction::Jmp(offset(operands[0])?),
+            "set" => Instruction::Set(register(operands[0])?, operand(operands[1])?),
+            "add" => Instruction::Add(register(operands[0])?, operand(operands[1])?),
+            "mul" => Instruction::Mul(register(operands[0])?, operand(operands[1])?),
+            "jz" => Instruction::Jz(register(operands[0])?, offset(operands[1])?),
+            "jnz" => Instruction::Jnz(register(operands[0])?, offset(operands[1])?),
+            "out" => Instruction::Out(operand(operands[0])?),
+            _ => Instruction::Hlt,
+        })
+    }
+
+    /// Prints a program with the instruction pointer of each instruction, and
+    /// the resolved targets of all jumps.
+    pub fn disassemble(input: &str) -> Result<String, AssembleError> {
+        let instructions = assemble(input)?;
+        let len = instructions.len();
+        let mut output = String::new();
+        for (ip, instr) in instructions.iter().enumerate() {
+            let line = format!("{:>4}  {}", ip, instr);
+            let offset = match instr {
+                Instruction::Jmp(arg) | Instruction::Jz(_, arg) | Instruction::Jnz(_, arg) => *arg,
+                _ => {
+                    output.push_str(&line);
+                    output.push('\n');
+                    continue;
+                }
+            };
+            let target = match jump_target(len, ip, offset) {
+                Some(target) if target == len => "end".to_owned(),
+                Some(target) => target.to_string(),
+                None => match (ip as isize).checked_add(offset) {
+                    Some(target) => format!("{} (out of bounds)", target),
+                    None => "out of bounds".to_owned(),
+                },
+            };
+            output.push_str(&format!("{:<17} # -> {}\n", line, target));
+        }
+        Ok(output)
+    }
 
-        state.run(&instructions);
-        state.accumulator
+    pub fn a(input: &str) -> Result<isize, AssembleError> {
+        let mut program = Program::new(input)?;
+
+        program.run();
+        Ok(program.accumulator())
     }
 
     fn swap_nop_jmp(instr: &mut Instruction) {
@@ -88,33 +565,609 @@ mod day_08 {
         }
     }
 
-    pub fn b(input: &str) -> isize {
-        let mut instructions: Vec<_> = parse_instructions(input);
+    /// The target of jumping by `offset` from `ip`, unless that is out of bounds.
+    fn jump_target(len: usize, ip: usize, offset: isize) -> Option<usize> {
+        match (ip as isize).checked_add(offset) {
+            Some(target) if target >= 0 && target as usize <= len => Some(target as usize),
+            _ => None,
+        }
+    }
+
+    /// The instructions that can follow the one at `ip`, with the instruction
+    /// count meaning that the program terminates.
+    fn successors(instructions: &[Instruction], ip: usize) -> [Option<usize>; 2] {
+        let len = instructions.len();
+        match instructions[ip] {
+            Instruction::Jmp(arg) => [jump_target(len, ip, arg), None],
+            Instruction::Jz(_, arg) | Instruction::Jnz(_, arg) => {
+                [jump_target(len, ip, 1), jump_target(len, ip, arg)]
+            }
+            Instruction::Hlt => [Some(len), None],
+            _ => [jump_target(len, ip, 1), None],
+        }
+    }
+
+    /// Finds all the instructions from which execution can terminate.
+    ///
+    /// Conditional jumps may go either way, so this is only exact for boot code.
+    fn can_terminate(instructions: &[Instruction]) -> Vec<bool> {
+        let len = instructions.len();
+        let mut predecessors = vec![vec![]; len + 1];
+        for ip in 0..len {
+            for next in successors(instructions, ip).iter().flatten() {
+                predecessors[*next].push(ip);
+            }
+        }
+
+        let mut terminates = vec![false; len + 1];
+        terminates[len] = true;
+        let mut queue = vec![len];
+        while let Some(ip) = queue.pop() {
+            for prev in &predecessors[ip] {
+                if !terminates[*prev] {
+                    terminates[*prev] = true;
+                    queue.push(*prev);
+                }
+            }
+        }
+        terminates
+    }
+
+    /// How corrupted boot code can be repaired.
+    #[derive(Debug, PartialEq)]
+    pub enum Repair {
+        /// The program terminates as it is.
+        NotCorrupted,
+        /// Swapping this instruction makes the program terminate.
+        Swap(usize),
+        /// Swapping any one of these instructions makes the program terminate.
+        Ambiguous(Vec<usize>),
+        /// No single swap makes the program terminate.
+        Impossible,
+    }
+
+    /// Finds the `nop` and `jmp` instructions that make the program terminate
+    /// when swapped.
+    ///
+    /// Only instructions on the original execution path matter, and swapping one
+    /// of them fixes the program exactly if the swapped instruction continues at
+    /// an instruction that can terminate.
+    fn find_repair(instructions: &[Instruction]) -> Repair {
+        let len = instructions.len();
+        if len == 0 {
+            return Repair::NotCorrupted;
+        }
+        let terminates = can_terminate(instructions);
+        let mut visited = vec![false; len];
+        let mut repairs = vec![];
         let mut state = ProcessorState::default();
+        loop {
+            let ip = state.instruction_pointer;
+            match visited.get_mut(ip) {
+                Some(false) => visited[ip] = true,
+                _ => break,
+            }
+            let swapped_target = match instructions[ip] {
+                Instruction::Nop(arg) => jump_target(len, ip, arg),
+                Instruction::Jmp(_) => jump_target(len, ip, 1),
+                _ => None,
+            };
+            if swapped_target.is_some_and(|target| terminates[target]) {
+                repairs.push(ip);
+            }
+            match state.step(instructions) {
+                Some(Outcome::Terminated) | Some(Outcome::Halted) => return Repair::NotCorrupted,
+                Some(_) => break,
+                None => {}
+            }
+        }
 
-        if state.run(&instructions) {
-            return state.accumulator;
+        repairs.sort_unstable();
+        match repairs.len() {
+            0 => Repair::Impossible,
+            1 => Repair::Swap(repairs[0]),
+            _ => Repair::Ambiguous(repairs),
         }
+    }
 
-        for i in 0..instructions.len() {
-            state = ProcessorState::default();
-            if !matches!(
-                instructions.get(i),
-                Some(Instruction::Nop(_)) | Some(Instruction::Jmp(_))
-            ) {
-                continue;
+    pub fn repair(input: &str) -> Result<Repair, AssembleError> {
+        Ok(find_repair(&assemble(input)?))
+    }
+
+    pub fn b(input: &str) -> Result<isize, AssembleError> {
+        let mut program = Program::new(input)?;
+        match find_repair(&program.instructions) {
+            Repair::NotCorrupted => {}
+            Repair::Swap(ip) => program.swap(ip),
+            Repair::Ambiguous(ips) => program.swap(ips[0]),
+            Repair::Impossible => return Ok(0),
+        }
+
+        program.run();
+        Ok(program.accumulator())
+    }
+
+    /// Runs the boot code until it ends or loops, returning the outcome, the
+    /// number of executed instructions and the final accumulator.
+    pub fn run(input: &str) -> Result<(Outcome, usize, isize), AssembleError> {
+        let mut program = Program::new(input)?;
+        let outcome = program.run();
+        Ok((outcome, program.steps, program.accumulator()))
+    }
+
+    /// Runs a program for at most `max_steps` instructions, returning its output
+    /// if it terminated in time.
+    pub fn execute(input: &str, max_steps: usize) -> Result<Option<Vec<isize>>, AssembleError> {
+        let instructions = assemble(input)?;
+        let mut state = ProcessorState::default();
+        Ok(match state.run_for(&instructions, max_steps) {
+            Some(Outcome::Terminated) | Some(Outcome::Halted) => Some(state.output),
+            _ => None,
+        })
+    }
+
+    /// The control flow of a program, worked out without running it.
+    pub struct Analysis {
+        instructions: Vec<Instruction>,
+        reachable: Vec<bool>,
+        terminates: Vec<bool>,
+        loops: Vec<Vec<usize>>,
+    }
+
+    impl Analysis {
+        fn new(instructions: Vec<Instruction>) -> Self {
+            let len = instructions.len();
+            let mut reachable = vec![false; len + 1];
+            let mut queue = vec![0];
+            reachable[0] = true;
+            while let Some(ip) = queue.pop() {
+                if ip == len {
+                    continue;
+                }
+                for next in successors(&instructions, ip).iter().flatten() {
+                    if !reachable[*next] {
+                        reachable[*next] = true;
+                        queue.push(*next);
+                    }
+                }
             }
-            instructions.get_mut(i).map(swap_nop_jmp);
-            if state.run(&instructions) {
-                return state.accumulator;
+
+            let terminates = can_terminate(&instructions);
+            let loops = find_loops(&instructions);
+            Analysis {
+                instructions,
+                reachable,
+                terminates,
+                loops,
             }
-            instructions.get_mut(i).map(swap_nop_jmp);
         }
-        0
+
+        /// The instructions that no execution can get to.
+        pub fn unreachable(&self) -> Vec<usize> {
+            (0..self.instructions.len())
+                .filter(|ip| !self.reachable[*ip])
+                .collect()
+        }
+
+        /// The instructions from which execution can get to the end of the program.
+        pub fn terminating(&self) -> Vec<usize> {
+            (0..self.instructions.len())
+                .filter(|ip| self.terminates[*ip])
+                .collect()
+        }
+
+        /// The strongly connected components of the control flow graph that
+        /// execution can go around in, ordered by their first instruction.
+        pub fn loops(&self) -> &[Vec<usize>] {
+            &self.loops
+        }
+
+        pub fn to_dot(&self) -> String {
+            let len = self.instructions.len();
+            let in_loop: Vec<_> = (0..len)
+                .map(|ip| self.loops.iter().any(|ips| ips.contains(&ip)))
+                .collect();
+
+            let mut dot = String::from("digraph program {\n");
+            for (ip, instr) in self.instructions.iter().enumerate() {
+                let mut attributes = vec![format!("label=\"{}: {}\"", ip, instr)];
+                if !self.reachable[ip] {
+                    attributes.push("style=dashed".to_owned());
+                }
+                if in_loop[ip] {
+                    attributes.push("color=red".to_owned());
+                }
+                if self.terminates[ip] {
+                    attributes.push("fontcolor=darkgreen".to_owned());
+                }
+                dot.push_str(&format!("    {} [{}];\n", ip, attributes.join(", ")));
+            }
+            dot.push_str("    end [shape=doublecircle];\n");
+            for ip in 0..len {
+                for next in successors(&self.instructions, ip).iter().flatten() {
+                    if *next == len {
+                        dot.push_str(&format!("    {} -> end;\n", ip));
+                    } else {
+                        dot.push_str(&format!("    {} -> {};\n", ip, next));
+                    }
+                }
+            }
+            dot.push_str("}\n");
+            dot
+        }
+    }
+
+    /// Formats instruction pointers as comma separated ranges, like `1-4, 7`.
+    fn ranges(ips: &[usize]) -> String {
+        if ips.is_empty() {
+            return "none".to_owned();
+        }
+        let mut ranges = vec![];
+        let mut start = 0;
+        for i in 1..=ips.len() {
+            if i == ips.len() || ips[i] != ips[i - 1] + 1 {
+                if start == i - 1 {
+                    ranges.push(ips[start].to_string());
+                } else {
+                    ranges.push(format!("{}-{}", ips[start], ips[i - 1]));
+                }
+                start = i;
+            }
+        }
+        ranges.join(", ")
+    }
+
+    impl fmt::Display for Analysis {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let unreachable = self.unreachable();
+            writeln!(
+                f,
+                "{} instructions, {} reachable",
+                self.instructions.len(),
+                self.instructions.len() - unreachable.len()
+            )?;
+            writeln!(f, "unreachable: {}", ranges(&unreachable))?;
+            if self.loops.is_empty() {
+                writeln!(f, "loops: none")?;
+            }
+            for ips in &self.loops {
+                writeln!(f, "loop: {}", ranges(ips))?;
+            }
+            writeln!(f, "terminating: {}", ranges(&self.terminating()))
+        }
+    }
+
+    /// Finds the loops in a program as the strongly connected components of its
+    /// control flow graph.
+    fn find_loops(instructions: &[Instruction]) -> Vec<Vec<usize>> {
+        let len = instructions.len();
+        let successors = |ip| {
+            IntoIterator::into_iter(successors(instructions, ip))
+                .flatten()
+                .filter(move |next| *next < len)
+        };
+        let mut loops = cyclic_components(len, successors);
+        loops.sort_unstable();
+        loops
+    }
+
+    /// Analyses the control flow of a program.
+    pub fn analyze(input: &str) -> Result<Analysis, AssembleError> {
+        Ok(Analysis::new(assemble(input)?))
+    }
+
+    impl fmt::Display for Operand {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Operand::Value(value) => write!(f, "{:+}", value),
+                Operand::Register(reg) => write!(f, "{}", REGISTERS[*reg]),
+            }
+        }
+    }
+
+    impl fmt::Display for Instruction {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Instruction::Nop(arg) => write!(f, "nop {:+}", arg),
+                Instruction::Acc(arg) => write!(f, "acc {:+}", arg),
+                Instruction::Jmp(arg) => write!(f, "jmp {:+}", arg),
+                Instruction::Set(reg, op) => write!(f, "set {} {}", REGISTERS[*reg], op),
+                Instruction::Add(reg, op) => write!(f, "add {} {}", REGISTERS[*reg], op),
+                Instruction::Mul(reg, op) => write!(f, "mul {} {}", REGISTERS[*reg], op),
+                Instruction::Jz(reg, arg) => write!(f, "jz {} {:+}", REGISTERS[*reg], arg),
+                Instruction::Jnz(reg, arg) => write!(f, "jnz {} {:+}", REGISTERS[*reg], arg),
+                Instruction::Out(op) => write!(f, "out {}", op),
+                Instruction::Hlt => write!(f, "hlt"),
+            }
+        }
+    }
+
+    #[derive(Clone, Copy, Debug)]
+    enum Comparison {
+        Less,
+        LessOrEqual,
+        Equal,
+        NotEqual,
+        GreaterOrEqual,
+        Greater,
+    }
+
+    impl Comparison {
+        const ALL: [Comparison; 6] = [
+            Comparison::Less,
+            Comparison::LessOrEqual,
+            Comparison::Equal,
+            Comparison::NotEqual,
+            Comparison::GreaterOrEqual,
+            Comparison::Greater,
+        ];
+
+        fn symbol(self) -> &'static str {
+            match self {
+                Comparison::Less => "<",
+                Comparison::LessOrEqual => "<=",
+                Comparison::Equal => "==",
+                Comparison::NotEqual => "!=",
+                Comparison::GreaterOrEqual => ">=",
+                Comparison::Greater => ">",
+            }
+        }
+
+        fn holds(self, lhs: isize, rhs: isize) -> bool {
+            match self {
+                Comparison::Less => lhs < rhs,
+                Comparison::LessOrEqual => lhs <= rhs,
+                Comparison::Equal => lhs == rhs,
+                Comparison::NotEqual => lhs != rhs,
+                Comparison::GreaterOrEqual => lhs >= rhs,
+                Comparison::Greater => lhs > rhs,
+            }
+        }
+    }
+
+    /// A condition that pauses the debugger.
+    #[derive(Debug)]
+    enum Breakpoint {
+        /// Pauses before the instruction at this address.
+        Ip(usize),
+        /// Pauses as soon as the register compares to the value.
+        Register(usize, Comparison, isize),
+        /// Pauses before any instruction is executed a second time.
+        Loop,
+    }
+
+    impl fmt::Display for Breakpoint {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Breakpoint::Ip(ip) => write!(f, "ip {}", ip),
+                Breakpoint::Register(reg, cmp, value) => {
+                    write!(f, "{} {} {}", REGISTERS[*reg], cmp.symbol(), value)
+                }
+                Breakpoint::Loop => write!(f, "loop"),
+            }
+        }
+    }
+
+    fn parse_breakpoint(input: &str) -> Option<Breakpoint> {
+        let mut words = input.split_whitespace();
+        let breakpoint = match (words.next()?, words.next(), words.next()) {
+            ("loop", None, None) => Breakpoint::Loop,
+            ("ip", Some(ip), None) => Breakpoint::Ip(ip.parse().ok()?),
+            (reg, Some(op), Some(value)) => {
+                let cmp = Comparison::ALL.iter().find(|cmp| cmp.symbol() == op)?;
+                Breakpoint::Register(parse_register(reg)?, *cmp, value.parse().ok()?)
+            }
+            _ => return None,
+        };
+        match words.next() {
+            Some(_) => None,
+            None => Some(breakpoint),
+        }
+    }
+
+    /// The number of steps after which `continue` and `step` pause, even without
+    /// breakpoint.
+    const STEP_LIMIT: usize = 1_000_000;
+
+    /// Steps through a program, with optional tracing and breakpoints.
+    struct Debugger<'a> {
+        instructions: &'a [Instruction],
+        state: ProcessorState,
+        executed: Vec<bool>,
+        breakpoints: Vec<Breakpoint>,
+        trace: bool,
+        /// Why the program can not continue, once it stopped.
+        finished: Option<String>,
+    }
+
+    impl<'a> Debugger<'a> {
+        fn new(instructions: &'a [Instruction]) -> Self {
+            Self {
+                instructions,
+                state: ProcessorState::default(),
+                executed: vec![false; instructions.len()],
+                breakpoints: vec![],
+                trace: false,
+                finished: None,
+            }
+        }
+
+        /// Describes where execution currently is.
+        fn location(&self) -> String {
+            if let Some(finished) = &self.finished {
+                return finished.clone();
+            }
+            let ip = self.state.instruction_pointer;
+            format!("ip {}: {}", ip, self.instructions[ip])
+        }
+
+        /// Finds the breakpoint matching the current state.
+        fn breakpoint(&self) -> Option<&Breakpoint> {
+            let ip = self.state.instruction_pointer;
+            self.breakpoints.iter().find(|breakpoint| match breakpoint {
+                Breakpoint::Ip(bp) => *bp == ip,
+                Breakpoint::Register(reg, cmp, value) => {
+                    cmp.holds(self.state.registers[*reg], *value)
+                }
+                Breakpoint::Loop => self.executed.get(ip).copied().unwrap_or(false),
+            })
+        }
+
+        /// Executes a single instruction, returning `false` if the program stopped.
+        fn step(&mut self, out: &mut dyn Write) -> io::Result<bool> {
+            if self.finished.is_some() {
+                return Ok(false);
+            }
+            let ip = self.state.instruction_pointer;
+            self.executed[ip] = true;
+            let result = self.state.step(self.instructions);
+            if self.trace {
+                writeln!(
+                    out,
+                    "{:>5}  {:<12} acc={}",
+                    ip,
+                    self.instructions[ip].to_string(),
+                    self.state.accumulator()
+                )?;
+            }
+            self.finished =
+                result.map(|outcome| format!("{} with acc={}", outcome, self.state.accumulator()));
+            Ok(self.finished.is_none())
+        }
+
+        /// Executes a single debugger command, returning `false` to quit.
+        fn command(&mut self, line: &str, out: &mut dyn Write) -> io::Result<bool> {
+            let (command, args) = split(line, " ").unwrap_or((line, ""));
+            match command {
+                "s" | "step" => {
+                    let steps: Result<usize, _> =
+                        if args.is_empty() { Ok(1) } else { args.parse() };
+                    match steps {
+                        Ok(steps) => {
+                            let mut executed = 0;
+                            while executed < steps.min(STEP_LIMIT) && self.step(out)? {
+                                executed += 1;
+                            }
+                            if executed == STEP_LIMIT {
+                                writeln!(out, "paused after {} steps", executed)?;
+                            }
+                            writeln!(out, "{}", self.location())?;
+                        }
+                        Err(_) => writeln!(out, "invalid step count: {}", args)?,
+                    }
+                }
+                "c" | "continue" => {
+                    let mut steps = 0;
+                    while self.step(out)? {
+                        if let Some(breakpoint) = self.breakpoint() {
+                            writeln!(out, "breakpoint {}", breakpoint)?;
+                            break;
+                        }
+                        steps += 1;
+                        if steps == STEP_LIMIT {
+                            writeln!(out, "paused after {} steps", steps)?;
+                            break;
+                        }
+                    }
+                    writeln!(out, "{}", self.location())?;
+                }
+                "p" | "print" => {
+                    write!(out, "ip={}", self.state.instruction_pointer)?;
+                    for (name, value) in REGISTERS.iter().zip(self.state.registers.iter()) {
+                        write!(out, " {}={}", name, value)?;
+                    }
+                    writeln!(out, " output={:?}", self.state.output)?;
+                    writeln!(out, "{}", self.location())?;
+                }
+                "set" => match split(args, " ") {
+                    Some(("ip", value)) if self.finished.is_none() => match value.parse() {
+                        Ok(ip) if ip < self.instructions.len() => {
+                            self.state.instruction_pointer = ip;
+                        }
+                        _ => writeln!(out, "invalid ip: {}", value)?,
+                    },
+                    Some(("ip", _)) => writeln!(out, "the program has ended, reset it first")?,
+                    Some((reg, value)) => match (parse_register(reg), value.parse()) {
+                        (Some(reg), Ok(value)) => self.state.registers[reg] = value,
+                        _ => writeln!(out, "invalid register assignment: {}", args)?,
+                    },
+                    None => writeln!(out, "usage: set <register> <value>")?,
+                },
+                "b" | "break" => match parse_breakpoint(args) {
+                    Some(breakpoint) => self.breakpoints.push(breakpoint),
+                    None => writeln!(
+                        out,
+                        "usage: break ip <ip> | break <register> <comparison> <value> | break loop"
+                    )?,
+                },
+                "delete" => self.breakpoints.clear(),
+                "reset" => {
+                    self.state = ProcessorState::default();
+                    self.executed.fill(false);
+                    self.finished = None;
+                    writeln!(out, "{}", self.location())?;
+                }
+                "trace" => match args {
+                    "on" => self.trace = true,
+                    "off" => self.trace = false,
+                    _ => writeln!(out, "usage: trace on|off")?,
+                },
+                "q" | "quit" => return Ok(false),
+                _ => writeln!(
+                    out,
+                    "commands: step [n], continue, print, set <register> <value>, \
+                     break <condition>, delete, reset, trace on|off, quit"
+                )?,
+            }
+            Ok(true)
+        }
+    }
+
+    /// Runs an interactive debugger session, reading commands from `commands`.
+    pub fn debug(
+        input: &str,
+        commands: impl BufRead,
+        mut out: impl Write,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let instructions = assemble(input)?;
+        if instructions.is_empty() {
+            return Ok(writeln!(out, "empty program")?);
+        }
+        let mut debugger = Debugger::new(&instructions);
+        writeln!(out, "{}", debugger.location())?;
+        write!(out, "> ")?;
+        out.flush()?;
+        for line in commands.lines() {
+            if !debugger.command(line?.trim(), &mut out)? {
+                break;
+            }
+            write!(out, "> ")?;
+            out.flush()?;
+        }
+        Ok(writeln!(out)?)
+    }
+
+    /// Runs a program until it terminates or loops, tracing every instruction.
+    pub fn trace(input: &str, mut out: impl Write) -> Result<(), Box<dyn std::error::Error>> {
+        let instructions = assemble(input)?;
+        if instructions.is_empty() {
+            return Ok(writeln!(out, "empty program")?);
+        }
+        let mut debugger = Debugger::new(&instructions);
+        debugger.trace = true;
+        debugger.breakpoints.push(Breakpoint::Loop);
+        debugger.command("continue", &mut out)?;
+        Ok(())
     }
 }
 
 mod day_09 {
+    use std::collections::{HashMap, VecDeque};
+    use std::fmt;
+    use std::io::{self, BufRead};
+    use std::str::FromStr;
+
+    /// The reference check: sorts a copy of the preamble for every number.
     fn has_sums_to(numbers: &[usize], needle: usize) -> bool {
         let mut numbers = Vec::from(numbers);
         numbers.sort_unstable();
@@ -123,18 +1176,234 @@ mod day_09 {
                 return false;
             }
             let pair = needle - num;
-            if matches!(numbers.binary_search(&pair), Ok(_)) {
+            if numbers.binary_search(&pair).is_ok() {
                 return true;
             }
         }
         false
     }
 
+    /// The last numbers of a stream, counted by value so that pairs can be
+    /// looked up without sorting.
+    struct Window {
+        len: usize,
+        numbers: VecDeque<usize>,
+        counts: HashMap<usize, usize>,
+    }
+
+    impl Window {
+        fn new(len: usize) -> Self {
+            Window {
+                len,
+                numbers: VecDeque::with_capacity(len + 1),
+                counts: HashMap::with_capacity(len + 1),
+            }
+        }
+
+        fn is_full(&self) -> bool {
+            self.numbers.len() == self.len
+        }
+
+        /// Adds a number, dropping the oldest one if the window is full.
+        fn push(&mut self, num: usize) {
+            self.numbers.push_back(num);
+            *self.counts.entry(num).or_default() += 1;
+            if self.numbers.len() > self.len {
+                let oldest = self.numbers.pop_front().unwrap_or(num);
+                if let Some(count) = self.counts.get_mut(&oldest) {
+                    *count -= 1;
+                    if *count == 0 {
+                        self.counts.remove(&oldest);
+                    }
+                }
+            }
+        }
+
+        /// Whether numbers of the window sum up to the needle according to the
+        /// rule. Pairs take a single pass over the distinct values.
+        fn has_sum(&self, needle: usize, rule: Rule) -> bool {
+            let pair = |num: &usize| needle.checked_sub(*num).map(|other| (*num, other));
+            match rule {
+                Rule::Lenient => self
+                    .counts
+                    .keys()
+                    .any(|num| *num <= needle / 2 && self.counts.contains_key(&(needle - num))),
+                Rule::Pair { equal_values } => {
+                    self.counts.keys().filter_map(pair).any(|(num, other)| {
+                        match self.counts.get(&other) {
+                            Some(count) if other == num => equal_values && *count > 1,
+                            Some(_) => true,
+                            None => false,
+                        }
+                    })
+                }
+                Rule::SumOf(k) => {
+                    let mut values: Vec<_> = self.counts.iter().map(|(v, c)| (*v, *c)).collect();
+                    values.sort_unstable();
+                    sums_to(&values, k, needle)
+                }
+            }
+        }
+    }
+
+    /// Whether `k` of the values, each used at most as often as it is counted,
+    /// sum up to the needle. This is exponential in `k`, but the sorted values
+    /// prune the search early.
+    fn sums_to(values: &[(usize, usize)], k: usize, needle: usize) -> bool {
+        if k == 0 {
+            return needle == 0;
+        }
+        let ((value, count), rest) = match values.split_first() {
+            Some(first) => first,
+            None => return false,
+        };
+        // all the other values are larger
+        if value.saturating_mul(k) > needle {
+            return false;
+        }
+        (0..=k.min(*count)).any(|copies| {
+            value
+                .checked_mul(copies)
+                .and_then(|sum| needle.checked_sub(sum))
+                .is_some_and(|needle| sums_to(rest, k - copies, needle))
+        })
+    }
+
+    /// Which numbers from the window can sum up to a valid number.
+    #[derive(Clone, Copy, Debug)]
+    pub enum Rule {
+        /// Any two numbers, where a number may also be added to itself. This is
+        /// what `has_sums_to` checks.
+        Lenient,
+        /// Two numbers at different positions, which may have the same value or not.
+        Pair { equal_values: bool },
+        /// Any `k` numbers at different positions.
+        SumOf(usize),
+    }
+
+    impl FromStr for Rule {
+        type Err = String;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            Ok(match s {
+                "lenient" => Rule::Lenient,
+                "pair" => Rule::Pair { equal_values: true },
+                "distinct" => Rule::Pair {
+                    equal_values: false,
+                },
+                _ => {
+                    let k = s
+                        .strip_prefix("sum-of-")
+                        .and_then(|k| k.parse().ok())
+                        .ok_or_else(|| format!("unknown rule: {}", s))?;
+                    Rule::SumOf(k)
+                }
+            })
+        }
+    }
+
+    /// Checks numbers one at a time, as they arrive.
+    pub struct Validator {
+        preamble: Window,
+        rule: Rule,
+        count: usize,
+    }
+
+    impl Validator {
+        pub fn new(window: usize, rule: Rule) -> Self {
+            Validator {
+                preamble: Window::new(window),
+                rule,
+                count: 0,
+            }
+        }
+
+        /// Whether the next number is valid, which it is if it is part of the
+        /// preamble or if numbers in the window before it sum up to it.
+        fn is_valid(&self, num: usize) -> bool {
+            !self.preamble.is_full() || self.preamble.has_sum(num, self.rule)
+        }
+
+        fn push(&mut self, num: usize) {
+            self.preamble.push(num);
+            self.count += 1;
+        }
+
+        /// Checks the next number.
+        pub fn check(&mut self, num: usize) -> bool {
+            let valid = self.is_valid(num);
+            self.push(num);
+            valid
+        }
+    }
+
+    /// An error while reading a stream of numbers.
+    #[derive(Debug)]
+    pub enum StreamError {
+        Io(io::Error),
+        Parse { line: usize, text: String },
+    }
+
+    impl fmt::Display for StreamError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                StreamError::Io(error) => write!(f, "{}", error),
+                StreamError::Parse { line, text } => {
+                    write!(f, "invalid number {:?} on line {}", text, line)
+                }
+            }
+        }
+    }
+
+    impl std::error::Error for StreamError {}
+
+    impl From<io::Error> for StreamError {
+        fn from(error: io::Error) -> Self {
+            StreamError::Io(error)
+        }
+    }
+
+    /// Reads numbers from a stream, yielding the invalid ones with their index
+    /// as soon as they are read. Blank lines are skipped.
+    pub fn stream_invalid(
+        input: impl BufRead,
+        window: usize,
+        rule: Rule,
+    ) -> impl Iterator<Item = Result<(usize, usize), StreamError>> {
+        let mut validator = Validator::new(window, rule);
+        input.lines().enumerate().filter_map(move |(i, line)| {
+            let line = match line {
+                Ok(line) => line,
+                Err(error) => return Some(Err(error.into())),
+            };
+            let text = line.trim();
+            if text.is_empty() {
+                return None;
+            }
+            let num = match text.parse() {
+                Ok(num) => num,
+                Err(_) => {
+                    return Some(Err(StreamError::Parse {
+                        line: i + 1,
+                        text: text.to_owned(),
+                    }))
+                }
+            };
+            let index = validator.count;
+            (!validator.check(num)).then_some(Ok((index, num)))
+        })
+    }
+
     fn parse_numbers(input: &str) -> Vec<usize> {
         input.lines().filter_map(|line| line.parse().ok()).collect()
     }
 
-    fn find_invalid(numbers: &[usize], window: usize) -> usize {
+    fn find_invalid(numbers: &[usize], window: usize) -> Option<usize> {
+        let mut validator = Validator::new(window, Rule::Lenient);
+        numbers.iter().copied().find(|num| !validator.check(*num))
+    }
+
+    fn find_invalid_by_sorting(numbers: &[usize], window: usize) -> usize {
         for window in numbers.windows(window + 1) {
             let needle = window.last().copied().unwrap_or(0);
             if !has_sums_to(&window[..window.len() - 1], needle) {
@@ -144,38 +1413,88 @@ mod day_09 {
         0
     }
 
-    pub fn a(input: &str, window: usize) -> usize {
+    /// Finds all the invalid numbers with their index and the window before them.
+    pub fn all_invalid(input: &str, window: usize, rule: Rule) -> Vec<(usize, usize, Vec<usize>)> {
+        let mut validator = Validator::new(window, rule);
+        let mut invalid = vec![];
+        for (index, num) in parse_numbers(input).into_iter().enumerate() {
+            if !validator.is_valid(num) {
+                let preamble = validator.preamble.numbers.iter().copied().collect();
+                invalid.push((index, num, preamble));
+            }
+            validator.push(num);
+        }
+        invalid
+    }
+
+    /// Part A with the reference implementation, for checking the sliding window.
+    pub fn a_by_sorting(input: &str, window: usize) -> usize {
+        find_invalid_by_sorting(&parse_numbers(input), window)
+    }
+
+    /// Finds the first number that is not the sum of two in the window before it.
+    pub fn first_invalid(input: &str, window: usize) -> Option<usize> {
         find_invalid(&parse_numbers(input), window)
     }
 
-    pub fn b(input: &str, window: usize) -> usize {
-        let numbers = parse_numbers(input);
-        let invalid = find_invalid(&numbers, window);
-        let mut numbers_slice = numbers.as_slice();
-        while !numbers_slice.is_empty() {
-            let mut sum = 0;
-            let mut smallest = std::usize::MAX;
-            let mut largest = 0;
-            for n in numbers_slice.iter().copied() {
-                smallest = smallest.min(n);
-                largest = largest.max(n);
-                sum += n;
-                if sum == invalid {
-                    return smallest + largest;
-                }
-                if sum > invalid {
+    pub fn a(input: &str, window: usize) -> usize {
+        first_invalid(input, window).unwrap_or(0)
+    }
+
+    /// Finds all the contiguous ranges of at least two numbers that sum up to
+    /// the target, as inclusive indices ordered by their end.
+    ///
+    /// As the numbers are not negative, the sum of a range only grows with its
+    /// end and shrinks with its start, so two pointers find them in a single
+    /// pass. Only leading zeros can make several ranges end at the same number.
+    fn find_ranges(numbers: &[usize], target: usize) -> Vec<(usize, usize)> {
+        let mut ranges = vec![];
+        let mut start = 0;
+        let mut sum = 0;
+        for (end, num) in numbers.iter().enumerate() {
+            sum += num;
+            while sum > target && start <= end {
+                sum -= numbers[start];
+                start += 1;
+            }
+            if sum != target || start >= end {
+                continue;
+            }
+            for (first, num) in numbers[start..end].iter().enumerate() {
+                ranges.push((start + first, end));
+                if *num != 0 {
                     break;
                 }
             }
-            numbers_slice = &numbers_slice[1..];
         }
-        0
+        ranges
+    }
+
+    pub fn ranges(input: &str, target: usize) -> Vec<(usize, usize)> {
+        find_ranges(&parse_numbers(input), target)
+    }
+
+    pub fn b(input: &str, window: usize) -> usize {
+        let numbers = parse_numbers(input);
+        let invalid = match find_invalid(&numbers, window) {
+            Some(invalid) => invalid,
+            None => return 0,
+        };
+        match find_ranges(&numbers, invalid).first() {
+            Some((start, end)) => {
+                let range = &numbers[*start..=*end];
+                range.iter().min().unwrap_or(&0) + range.iter().max().unwrap_or(&0)
+            }
+            None => 0,
+        }
     }
 }
 
 mod day_10 {
     use std::collections::HashMap;
 
+    use crate::utils::Rng;
+
     fn parse_sorted_numbers(input: &str) -> Vec<usize> {
         let mut numbers: Vec<_> = input.lines().filter_map(|line| line.parse().ok()).collect();
         numbers.push(0);
@@ -218,6 +1537,71 @@ mod day_10 {
         }
         map.get(&last).copied().unwrap_or(0)
     }
+
+    /// All the ways to chain the adapters from the outlet to the device.
+    pub struct Arrangements {
+        /// The outlet, the distinct adapters and the device, sorted.
+        joltages: Vec<usize>,
+        /// The number of chains from each joltage to the device.
+        ways: Vec<usize>,
+    }
+
+    impl Arrangements {
+        /// Counts the chains from the device backwards, or returns `None` if
+        /// there are too many of them to count.
+        pub fn new(input: &str) -> Option<Self> {
+            let mut joltages = parse_sorted_numbers(input);
+            joltages.dedup();
+            let mut ways = vec![0_usize; joltages.len()];
+            ways[joltages.len() - 1] = 1;
+            for i in (0..joltages.len() - 1).rev() {
+                for j in (i + 1..joltages.len()).take_while(|j| joltages[*j] - joltages[i] <= 3) {
+                    ways[i] = ways[j].checked_add(ways[i])?;
+                }
+            }
+            Some(Arrangements { joltages, ways })
+        }
+
+        pub fn count(&self) -> usize {
+            self.ways[0]
+        }
+
+        /// Finds the `k`-th chain in lexicographic order, without the outlet and
+        /// the device, by skipping over the counted chains that come before it.
+        pub fn nth(&self, mut k: usize) -> Option<Vec<usize>> {
+            if k >= self.count() {
+                return None;
+            }
+            let last = self.joltages.len() - 1;
+            let mut chain = vec![];
+            let mut i = 0;
+            while i != last {
+                let mut next = i + 1;
+                while k >= self.ways[next] {
+                    k -= self.ways[next];
+                    next += 1;
+                }
+                i = next;
+                if i != last {
+                    chain.push(self.joltages[i]);
+                }
+            }
+            Some(chain)
+        }
+
+        /// Lazily enumerates all the chains in lexicographic order.
+        pub fn iter(&self) -> impl Iterator<Item = Vec<usize>> + '_ {
+            (0..self.count()).map_while(move |k| self.nth(k))
+        }
+
+        /// Picks one of the chains uniformly at random.
+        pub fn sample(&self, rng: &mut Rng) -> Option<Vec<usize>> {
+            match self.count() {
+                0 => None,
+                count => self.nth(rng.below(count as u64) as usize),
+            }
+        }
+    }
 }
 
 mod day_11 {
@@ -254,7 +1638,7 @@ mod day_11 {
     impl<T> Matrix<T> {
         pub fn from_column_vec(data: Vec<T>, columns: usize) -> Option<Self> {
             let len = data.len();
-            if len % columns != 0 {
+            if !len.is_multiple_of(columns) {
                 return None;
             }
             Some(Self {
@@ -539,14 +1923,323 @@ mod day_12 {
 }
 
 pub fn run() -> Result<(), Box<dyn std::error::Error>> {
-    assert_eq!(day_08::a(&example("08")?), 5);
-    assert_eq!(day_08::b(&example("08")?), 8);
-
-    println!("day 08 a: {:?}", day_08::a(&input("08")?));
-    println!("day 08 b: {:?}", day_08::b(&input("08")?));
+    assert_eq!(day_08::a(&example("08")?)?, 5);
+    assert_eq!(day_08::b(&example("08")?)?, 8);
+    let factorial = "set x +5\nset acc +1\nmul acc x\nadd x -1\njnz x -2\nout acc\nhlt\nout x";
+    assert_eq!(day_08::execute(factorial, 100)?, Some(vec![120]));
+    assert_eq!(day_08::execute(factorial, 10)?, None);
+    assert_eq!(
+        day_08::execute("set y +3\nout y\nadd y -1\njz y +2\njmp -3", 100)?,
+        Some(vec![3, 2, 1])
+    );
+    assert_eq!(day_08::execute(&example("08")?, 100)?, None);
+    assert_eq!(day_08::repair(&example("08")?)?, day_08::Repair::Swap(7));
+    assert_eq!(
+        day_08::run(&example("08")?)?,
+        (day_08::Outcome::LoopDetected { ip: 1 }, 7, 5)
+    );
+    assert_eq!(
+        day_08::run("acc +3\njmp +2\nacc +4")?,
+        (day_08::Outcome::Terminated, 2, 3)
+    );
+    assert_eq!(
+        day_08::run("acc +1\njmp -2")?,
+        (day_08::Outcome::OutOfBounds { ip: 1, target: -1 }, 2, 1)
+    );
+    assert_eq!(
+        day_08::run("acc +1\njmp +5\nacc +1")?,
+        (day_08::Outcome::OutOfBounds { ip: 1, target: 6 }, 2, 1)
+    );
+    assert_eq!(
+        day_08::run("acc +2\nhlt\nacc +1")?,
+        (day_08::Outcome::Halted, 2, 2)
+    );
+    assert_eq!(
+        day_08::repair("nop +2\njmp +0\nacc +1")?,
+        day_08::Repair::Ambiguous(vec![0, 1])
+    );
+    assert_eq!(
+        day_08::repair("acc +1\njmp +1")?,
+        day_08::Repair::NotCorrupted
+    );
+    assert_eq!(
+        day_08::repair("jmp +0\njmp -1")?,
+        day_08::Repair::Impossible
+    );
+    assert_eq!(day_08::repair("")?, day_08::Repair::NotCorrupted);
+    assert_eq!(
+        day_08::run("# nothing to do")?,
+        (day_08::Outcome::Terminated, 0, 0)
+    );
+    let labelled = "# counts down from three
+                    set y +3
+                    loop: out y  # the loop body
+                    add y -1
+                    jz y done
+                    jmp loop
+                    done:";
+    assert_eq!(day_08::execute(labelled, 100)?, Some(vec![3, 2, 1]));
+    assert_eq!(
+        day_08::execute("nop +0\n\nacc +1 +2", 100)
+            .unwrap_err()
+            .to_string(),
+        "line 3: `acc` expects 1 operands, got 2"
+    );
+    assert_eq!(
+        day_08::run("acc +1\njmp +1\nhcf").unwrap_err().to_string(),
+        "line 3: unknown opcode `hcf`"
+    );
+    assert_eq!(
+        day_08::run("jmp end\nend: jmp start")
+            .unwrap_err()
+            .to_string(),
+        "line 2: unknown label `start`"
+    );
+    assert_eq!(
+        day_08::run("a: nop +0\na: nop +0").unwrap_err().to_string(),
+        "line 2: duplicate label `a`"
+    );
+    assert_eq!(
+        day_08::disassemble(&example("08")?)?,
+        "   0  nop +0
+   1  acc +1
+   2  jmp +4      # -> 6
+   3  acc +3
+   4  jmp -3      # -> 1
+   5  acc -99
+   6  acc +1
+   7  jmp -4      # -> 3
+   8  acc +6
+"
+    );
+    assert_eq!(
+        day_08::disassemble("jz x +2\njmp -2")?,
+        "   0  jz x +2     # -> end
+   1  jmp -2      # -> -1 (out of bounds)
+"
+    );
+    let analysis = day_08::analyze(&example("08")?)?;
+    assert_eq!(analysis.unreachable(), vec![5, 8]);
+    assert_eq!(analysis.loops(), [vec![1, 2, 3, 4, 6, 7]]);
+    assert_eq!(analysis.terminating(), vec![8]);
+    assert_eq!(
+        analysis.to_string(),
+        "9 instructions, 7 reachable
+unreachable: 5, 8
+loop: 1-4, 6-7
+terminating: 8
+"
+    );
+    assert_eq!(
+        day_08::analyze("jmp +0\nset x +2\nadd x -1\njnz x -1\nhlt\njmp -1")?.to_string(),
+        "6 instructions, 1 reachable
+unreachable: 1-5
+loop: 0
+loop: 2-3
+terminating: 1-5
+"
+    );
+    assert_eq!(
+        day_08::analyze("nop +1\njz x +2\nacc +1")?.to_dot(),
+        "digraph program {
+    0 [label=\"0: nop +1\", fontcolor=darkgreen];
+    1 [label=\"1: jz x +2\", fontcolor=darkgreen];
+    2 [label=\"2: acc +1\", fontcolor=darkgreen];
+    end [shape=doublecircle];
+    0 -> 1;
+    1 -> 2;
+    1 -> end;
+    2 -> end;
+}
+"
+    );
+    for seed in 0..20 {
+        let generated = day_08::generate(200, seed);
+        let mut program = day_08::Program::new(&generated)?;
+        let repairs = program.brute_force_repairs(day_08::Engine::Compiled);
+        assert_eq!(
+            program.brute_force_repairs(day_08::Engine::Interpreter),
+            repairs
+        );
+        let expected = match program.run() {
+            day_08::Outcome::Terminated => day_08::Repair::NotCorrupted,
+            _ if repairs.is_empty() => day_08::Repair::Impossible,
+            _ if repairs.len() == 1 => day_08::Repair::Swap(repairs[0]),
+            _ => day_08::Repair::Ambiguous(repairs),
+        };
+        assert_eq!(day_08::repair(&generated)?, expected);
+    }
+    let overflow = "set x +3\nmul x x\nadd acc +9223372036854775807\njmp -2";
+    let mut session = vec![];
+    day_08::debug(overflow, "c\np".as_bytes(), &mut session)?;
+    assert!(String::from_utf8(session)?.contains("ip 1: mul x x"));
+    assert_eq!(
+        day_08::run(overflow)?,
+        (
+            day_08::Outcome::LoopDetected { ip: 1 },
+            4,
+            i64::MAX as isize
+        )
+    );
+    let far = "nop +0\njmp +9223372036854775807";
+    assert_eq!(
+        day_08::run(far)?,
+        (
+            day_08::Outcome::OutOfBounds {
+                ip: 1,
+                target: isize::MAX
+            },
+            2,
+            0
+        )
+    );
+    assert_eq!(day_08::execute(far, 10)?, None);
+    assert_eq!(
+        day_08::disassemble(far)?,
+        "   0  nop +0
+   1  jmp +9223372036854775807 # -> out of bounds
+"
+    );
+    assert_eq!(day_08::analyze(far)?.terminating(), vec![]);
+    let mut trace = vec![];
+    day_08::trace(&example("08")?, &mut trace)?;
+    assert_eq!(
+        String::from_utf8(trace)?,
+        "    0  nop +0       acc=0
+    1  acc +1       acc=1
+    2  jmp +4       acc=1
+    6  acc +1       acc=2
+    7  jmp -4       acc=2
+    3  acc +3       acc=5
+    4  jmp -3       acc=5
+breakpoint loop
+ip 1: acc +1
+"
+    );
+    let mut session = vec![];
+    day_08::debug(
+        "acc +1\nacc +2",
+        "c\nset ip 0\nreset\nset ip 1\nc".as_bytes(),
+        &mut session,
+    )?;
+    assert_eq!(
+        String::from_utf8(session)?,
+        "ip 0: acc +1\n\
+         > program terminated with acc=3\n\
+         > the program has ended, reset it first\n\
+         > ip 0: acc +1\n\
+         > > program terminated with acc=2\n\
+         > \n"
+    );
+    let mut session = vec![];
+    day_08::debug(
+        "jmp +0",
+        "step -3\nstep 3000000000".as_bytes(),
+        &mut session,
+    )?;
+    assert_eq!(
+        String::from_utf8(session)?,
+        "ip 0: jmp +0\n\
+         > invalid step count: -3\n\
+         > paused after 1000000 steps\nip 0: jmp +0\n\
+         > \n"
+    );
+    let mut session = vec![];
+    let commands = "break acc > 1\nc\nstep 2\ndelete\nbreak ip 2\nc\n\
+                    set acc -10\nset ip 8\np\nc\nq\nstep";
+    day_08::debug(&example("08")?, commands.as_bytes(), &mut session)?;
+    assert_eq!(
+        String::from_utf8(session)?,
+        "ip 0: nop +0\n\
+         > > breakpoint acc > 1\nip 7: jmp -4\n\
+         > ip 4: jmp -3\n\
+         > > > breakpoint ip 2\nip 2: jmp +4\n\
+         > > > ip=8 acc=-10 x=0 y=0 z=0 output=[]\nip 8: acc +6\n\
+         > program terminated with acc=-4\n\
+         > \n"
+    );
+
+    println!("day 08 a: {:?}", day_08::a(&input("08")?)?);
+    println!("day 08 b: {:?}", day_08::b(&input("08")?)?);
 
     assert_eq!(day_09::a(&example("09")?, 5), 127);
     assert_eq!(day_09::b(&example("09")?, 5), 62);
+    assert_eq!(
+        day_09::stream_invalid(example("09")?.as_bytes(), 5, day_09::Rule::Lenient)
+            .collect::<Result<Vec<_>, _>>()?,
+        vec![(14, 127)]
+    );
+    assert_eq!(
+        day_09::stream_invalid("1\n2\n\n3\n7\n5\n".as_bytes(), 2, day_09::Rule::Lenient)
+            .collect::<Result<Vec<_>, _>>()?,
+        vec![(3, 7), (4, 5)]
+    );
+    assert_eq!(
+        day_09::stream_invalid("1\n2\n5\nfive\n".as_bytes(), 2, day_09::Rule::Lenient)
+            .map(|invalid| invalid.map_err(|error| error.to_string()))
+            .collect::<Vec<_>>(),
+        vec![
+            Ok((2, 5)),
+            Err("invalid number \"five\" on line 4".to_owned())
+        ]
+    );
+    let rules = ["lenient", "pair", "distinct", "sum-of-2", "sum-of-3"];
+    let invalid: Vec<Vec<_>> = rules
+        .iter()
+        .map(|rule| {
+            let rule = rule.parse()?;
+            let invalid = day_09::all_invalid("1\n2\n1\n2\n4\n6\n3\n2", 3, rule);
+            Ok(invalid
+                .into_iter()
+                .map(|(index, num, _)| (index, num))
+                .collect())
+        })
+        .collect::<Result<_, String>>()?;
+    assert_eq!(
+        invalid,
+        [
+            vec![(6, 3), (7, 2)],
+            vec![(6, 3), (7, 2)],
+            vec![(3, 2), (4, 4), (6, 3), (7, 2)],
+            vec![(6, 3), (7, 2)],
+            vec![(3, 2), (4, 4), (5, 6), (6, 3), (7, 2)],
+        ]
+    );
+    assert_eq!(
+        day_09::all_invalid(&example("09")?, 5, day_09::Rule::Lenient),
+        vec![(14, 127, vec![95, 102, 117, 150, 182])]
+    );
+    assert_eq!(
+        day_09::a(&input("09")?, 25),
+        day_09::all_invalid(&input("09")?, 25, "distinct".parse()?)[0].1
+    );
+    assert_eq!(day_09::ranges(&example("09")?, 127), vec![(2, 5)]);
+    assert_eq!(
+        day_09::ranges("1\n2\n3\n0\n0\n3\n3\n6", 6),
+        vec![(0, 2), (0, 3), (0, 4), (2, 5), (3, 6), (4, 6), (5, 6)]
+    );
+    assert_eq!(day_09::ranges("0\n0\n0", 0), vec![(0, 1), (0, 2), (1, 2)]);
+    assert_eq!(day_09::ranges("1\n2\n3", 0), vec![]);
+    assert_eq!(day_09::first_invalid("1\n2\n3\n5\n8", 2), None);
+    assert_eq!(day_09::b("1\n2\n3\n5\n8", 2), 0);
+    assert_eq!(day_09::ranges("5\n1\n5", 5), vec![]);
+    let mut rng = Rng::new(9);
+    for window in [0, 1, 2, 5, 25, 100] {
+        for range in [10, 1_000, 1_000_000] {
+            // small ranges have plenty of repeated numbers and pairs of equal ones
+            let numbers: String = (0..2000)
+                .map(|_| format!("{}\n", rng.below(range)))
+                .collect();
+            assert_eq!(
+                day_09::a(&numbers, window),
+                day_09::a_by_sorting(&numbers, window)
+            );
+            assert_eq!(
+                day_09::all_invalid(&numbers, window, "pair".parse()?),
+                day_09::all_invalid(&numbers, window, "sum-of-2".parse()?)
+            );
+        }
+    }
 
     println!("day 09 a: {:?}", day_09::a(&input("09")?, 25));
     println!("day 09 b: {:?}", day_09::b(&input("09")?, 25));
@@ -556,6 +2249,31 @@ pub fn run() -> Result<(), Box<dyn std::error::Error>> {
     assert_eq!(day_10::b(&example("10a1")?), 8);
     assert_eq!(day_10::b(&example("10a2")?), 19208);
 
+    let arrangements = day_10::Arrangements::new(&example("10a1")?).ok_or("too many")?;
+    assert_eq!(arrangements.count(), 8);
+    let chains: Vec<_> = arrangements.iter().collect();
+    assert_eq!(chains.len(), 8);
+    assert!(chains.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_eq!(chains[0], vec![1, 4, 5, 6, 7, 10, 11, 12, 15, 16, 19]);
+    assert_eq!(chains[7], vec![1, 4, 7, 10, 12, 15, 16, 19]);
+    assert_eq!(arrangements.nth(8), None);
+    let mut counts = vec![0; chains.len()];
+    let mut rng = Rng::new(10);
+    for _ in 0..8000 {
+        let chain = arrangements.sample(&mut rng).ok_or("no chains")?;
+        counts[chains.binary_search(&chain).map_err(|_| "unknown chain")?] += 1;
+    }
+    assert!(counts.iter().all(|count| (850..1150).contains(count)));
+    let arrangements = day_10::Arrangements::new(&example("10a2")?).ok_or("too many")?;
+    assert_eq!(arrangements.count(), 19208);
+    assert_eq!(arrangements.iter().count(), 19208);
+    assert_eq!(
+        day_10::Arrangements::new("1\n5").map(|a| a.count()),
+        Some(0)
+    );
+    let adapters: String = (1..=200).map(|n| format!("{}\n", n)).collect();
+    assert!(day_10::Arrangements::new(&adapters).is_none());
+
     println!("day 10 a: {:?}", day_10::a(&input("10")?));
     println!("day 10 b: {:?}", day_10::b(&input("10")?));
 
@@ -573,3 +2291,123 @@ pub fn run() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+/// Parses the window size argument of the day 9 tools, which needs to be positive.
+fn window_arg(arg: Option<&String>) -> Result<usize, Box<dyn std::error::Error>> {
+    match arg.ok_or("missing window size")?.parse()? {
+        0 => Err("the window size needs to be positive".into()),
+        window => Ok(window),
+    }
+}
+
+/// Runs one of the additional tools of the given day.
+///
+/// Returns `false` if this week has no such day or tool.
+pub fn tool(day: &str, args: &[String]) -> Result<bool, Box<dyn std::error::Error>> {
+    match (day, args.first().map(String::as_str)) {
+        ("08", Some("trace")) => {
+            let input = read_input(day, args.get(1))?;
+            day_08::trace(&input, std::io::stdout().lock())?;
+        }
+        ("08", Some("run")) => {
+            let (outcome, steps, accumulator) = day_08::run(&read_input(day, args.get(1))?)?;
+            println!("{} after {} steps with acc={}", outcome, steps, accumulator);
+        }
+        ("08", Some("repair")) => {
+            let input = read_input(day, args.get(1))?;
+            match day_08::repair(&input)? {
+                day_08::Repair::NotCorrupted => println!("the program terminates already"),
+                day_08::Repair::Swap(ip) => println!("swap the instruction at ip {}", ip),
+                day_08::Repair::Ambiguous(ips) => {
+                    println!("swapping any of the instructions at ips {:?} works", ips)
+                }
+                day_08::Repair::Impossible => println!("no single swap fixes the program"),
+            }
+        }
+        ("08", Some("disasm")) => {
+            print!("{}", day_08::disassemble(&read_input(day, args.get(1))?)?);
+        }
+        ("08", Some("analyze")) => {
+            let analysis = day_08::analyze(&read_input(day, args.get(2))?)?;
+            match args.get(1).map(String::as_str) {
+                Some("text") => print!("{}", analysis),
+                Some("dot") => print!("{}", analysis.to_dot()),
+                _ => return Err("usage: 08 analyze <text|dot> [path]".into()),
+            }
+        }
+        ("08", Some("bench")) => {
+            let len = args.get(1).map_or(Ok(10_000), |len| len.parse())?;
+            let mut program = day_08::Program::new(&day_08::generate(len, 8))?;
+
+            let start = Instant::now();
+            let interpreted = program.brute_force_repairs(day_08::Engine::Interpreter);
+            println!("interpreter: {:?}", start.elapsed());
+
+            let start = Instant::now();
+            let compiled = program.brute_force_repairs(day_08::Engine::Compiled);
+            println!("compiled:    {:?}", start.elapsed());
+
+            assert_eq!(interpreted, compiled);
+            println!("{} possible repairs", compiled.len());
+        }
+        ("09", Some("stream")) => {
+            let window = window_arg(args.get(1))?;
+            let rule = args.get(2).ok_or("missing rule")?.parse()?;
+            let mut out = std::io::stdout().lock();
+            for invalid in day_09::stream_invalid(open_input(day, args.get(3))?, window, rule) {
+                let (index, num) = invalid?;
+                writeln!(out, "{}: {}", index, num)?;
+                out.flush()?;
+            }
+        }
+        ("09", Some("invalid")) => {
+            let window = window_arg(args.get(1))?;
+            let rule = args.get(2).ok_or("missing rule")?.parse()?;
+            let input = read_input(day, args.get(3))?;
+            let mut out = std::io::stdout().lock();
+            for (index, num, preamble) in day_09::all_invalid(&input, window, rule) {
+                writeln!(out, "{}: {} after {:?}", index, num, preamble)?;
+            }
+        }
+        ("09", Some("ranges")) => {
+            let window = window_arg(args.get(1))?;
+            let input = read_input(day, args.get(2))?;
+            let invalid = day_09::first_invalid(&input, window).ok_or("no invalid number")?;
+            let mut out = std::io::stdout().lock();
+            for (start, end) in day_09::ranges(&input, invalid) {
+                writeln!(out, "{}..={} sum up to {}", start, end, invalid)?;
+            }
+        }
+        ("10", Some("arrangements")) => {
+            let input = read_input(day, args.get(1))?;
+            let arrangements = day_10::Arrangements::new(&input).ok_or("too many arrangements")?;
+            let mut out = std::io::stdout().lock();
+            for chain in arrangements.iter() {
+                writeln!(out, "{:?}", chain)?;
+            }
+        }
+        ("10", Some("nth")) => {
+            let k = args.get(1).ok_or("missing index")?.parse()?;
+            let input = read_input(day, args.get(2))?;
+            let arrangements = day_10::Arrangements::new(&input).ok_or("too many arrangements")?;
+            let chain = arrangements.nth(k).ok_or("not that many arrangements")?;
+            println!("{:?}", chain);
+        }
+        ("10", Some("sample")) => {
+            let seed = args.get(1).ok_or("missing seed")?.parse()?;
+            let input = read_input(day, args.get(2))?;
+            let arrangements = day_10::Arrangements::new(&input).ok_or("too many arrangements")?;
+            let chain = arrangements
+                .sample(&mut Rng::new(seed))
+                .ok_or("no arrangements")?;
+            println!("{:?}", chain);
+        }
+        ("08", Some("debug")) => {
+            // the program can not come from stdin, as that is where the commands come from
+            let input = read_input(day, args.get(1).filter(|path| *path != "-"))?;
+            day_08::debug(&input, std::io::stdin().lock(), std::io::stdout().lock())?;
+        }
+        _ => return Ok(false),
+    }
+    Ok(true)
+}